@@ -0,0 +1,8 @@
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    scope VARCHAR NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE
+)
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE sessions DROP COLUMN user_id;
+
+ALTER TABLE users
+    DROP COLUMN role,
+    DROP COLUMN password;
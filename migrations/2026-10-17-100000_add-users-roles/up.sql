@@ -0,0 +1,7 @@
+ALTER TABLE users
+    ADD COLUMN role VARCHAR NOT NULL DEFAULT 'MEMBER',
+    ADD COLUMN password VARCHAR;
+
+ALTER TABLE sessions
+    ADD COLUMN user_id UUID,
+    ADD CONSTRAINT user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
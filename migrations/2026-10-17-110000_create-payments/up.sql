@@ -0,0 +1,12 @@
+CREATE TABLE payments (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    sanction_id UUID NOT NULL,
+    amount REAL NOT NULL,
+    created_at DATE NOT NULL default CURRENT_DATE,
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE,
+    CONSTRAINT user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    CONSTRAINT sanction_id FOREIGN KEY (sanction_id) REFERENCES sanctions (id) ON DELETE CASCADE
+)
\ No newline at end of file
@@ -0,0 +1,9 @@
+DROP TRIGGER charged_month ON sanctions;
+
+DROP FUNCTION set_charged_month();
+
+DROP FUNCTION charged_month(UUID, TEXT, DATE);
+
+DROP INDEX monthly_charge;
+
+ALTER TABLE sanctions DROP COLUMN charged_month;
@@ -0,0 +1,38 @@
+ALTER TABLE sanctions ADD COLUMN charged_month DATE;
+
+-- The month charged by a sanction of a monthly rule, on which a second charge
+-- is rejected.
+CREATE FUNCTION charged_month(UUID, TEXT, DATE) RETURNS DATE AS $$
+    SELECT DATE_TRUNC('month', $3::TIMESTAMP)::DATE
+    FROM teams, unnest(teams.rules) AS rules(rule)
+    WHERE teams.id = $1 AND rule->>'id' = $2 AND rule->'kind'->>'type' = 'MONTHLY'
+    LIMIT 1
+$$ LANGUAGE SQL;
+
+CREATE FUNCTION set_charged_month() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.charged_month := charged_month(NEW.team_id, NEW.sanction_info->>'associated_rule', NEW.created_at);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+UPDATE sanctions SET charged_month = charged_month(team_id, sanction_info->>'associated_rule', created_at);
+
+-- Only the first charge of a month is kept as such.
+UPDATE sanctions SET charged_month = NULL
+WHERE id IN (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (
+            PARTITION BY user_id, sanction_info->>'associated_rule', charged_month
+            ORDER BY created_at, id
+        ) AS charge
+        FROM sanctions
+        WHERE charged_month IS NOT NULL
+    ) AS charges
+    WHERE charge > 1
+);
+
+CREATE UNIQUE INDEX monthly_charge ON sanctions (user_id, (sanction_info->>'associated_rule'), charged_month);
+
+CREATE TRIGGER charged_month BEFORE INSERT OR UPDATE OF team_id, sanction_info, created_at ON sanctions
+    FOR EACH ROW EXECUTE PROCEDURE set_charged_month();
@@ -0,0 +1 @@
+DROP TABLE monthly_runs
\ No newline at end of file
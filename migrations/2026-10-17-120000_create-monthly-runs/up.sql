@@ -0,0 +1,9 @@
+CREATE TABLE monthly_runs (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    month DATE NOT NULL,
+    ran_at TIMESTAMP NOT NULL default CURRENT_TIMESTAMP,
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE,
+    CONSTRAINT month UNIQUE (team_id, month)
+)
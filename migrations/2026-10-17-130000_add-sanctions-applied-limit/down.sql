@@ -0,0 +1 @@
+ALTER TABLE sanctions DROP COLUMN applied_limit
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE sanctions ADD COLUMN applied_limit VARCHAR
\ No newline at end of file
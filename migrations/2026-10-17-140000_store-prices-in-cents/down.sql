@@ -0,0 +1,47 @@
+CREATE FUNCTION pg_temp.scale_price(price JSONB, factor NUMERIC) RETURNS JSONB AS $$
+    SELECT CASE
+        WHEN jsonb_typeof(price) = 'number' THEN to_jsonb(price::TEXT::NUMERIC * factor)
+        ELSE price
+    END
+$$ LANGUAGE SQL;
+
+CREATE FUNCTION pg_temp.scale_rule(rule JSONB, factor NUMERIC) RETURNS JSONB AS $$
+DECLARE
+    field TEXT;
+BEGIN
+    FOREACH field IN ARRAY ARRAY['price', 'price_to_multiply', 'price_per_time_unit'] LOOP
+        IF rule->'kind' ? field THEN
+            rule := jsonb_set(rule, ARRAY['kind', field], pg_temp.scale_price(rule->'kind'->field, factor));
+        END IF;
+    END LOOP;
+
+    IF jsonb_typeof(rule->'kind'->'tiers') = 'array' THEN
+        rule := jsonb_set(rule, '{kind,tiers}', (
+            SELECT COALESCE(jsonb_agg(pg_temp.scale_price(tier, factor) ORDER BY position), '[]')
+            FROM jsonb_array_elements(rule->'kind'->'tiers') WITH ORDINALITY AS tiers(tier, position)
+        ));
+    END IF;
+
+    FOREACH field IN ARRAY ARRAY['min_price', 'max_price'] LOOP
+        IF rule->'limits' ? field THEN
+            rule := jsonb_set(rule, ARRAY['limits', field], pg_temp.scale_price(rule->'limits'->field, factor));
+        END IF;
+    END LOOP;
+
+    IF jsonb_typeof(rule->'limits'->'period_cap') = 'object' THEN
+        rule := jsonb_set(rule, '{limits,period_cap,max_price}', pg_temp.scale_price(rule->'limits'->'period_cap'->'max_price', factor));
+    END IF;
+
+    RETURN rule;
+END
+$$ LANGUAGE plpgsql;
+
+UPDATE teams SET rules = ARRAY(
+    SELECT pg_temp.scale_rule(rule, 0.01)
+    FROM unnest(rules) WITH ORDINALITY AS rules(rule, position)
+    ORDER BY position
+);
+
+ALTER TABLE sanctions ALTER COLUMN price TYPE REAL USING price / 100.0;
+
+ALTER TABLE payments ALTER COLUMN amount TYPE REAL USING amount / 100.0;
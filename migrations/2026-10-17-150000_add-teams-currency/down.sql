@@ -0,0 +1 @@
+ALTER TABLE teams DROP COLUMN currency, DROP COLUMN locale
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE teams ADD COLUMN currency VARCHAR NOT NULL DEFAULT 'EUR', ADD COLUMN locale VARCHAR NOT NULL DEFAULT 'fr-FR'
\ No newline at end of file
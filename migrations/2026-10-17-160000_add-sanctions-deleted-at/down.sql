@@ -0,0 +1,5 @@
+DROP INDEX monthly_charge;
+
+ALTER TABLE sanctions DROP COLUMN deleted_at;
+
+CREATE UNIQUE INDEX monthly_charge ON sanctions (user_id, (sanction_info->>'associated_rule'), charged_month);
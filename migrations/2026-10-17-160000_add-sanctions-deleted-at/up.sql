@@ -0,0 +1,6 @@
+ALTER TABLE sanctions ADD COLUMN deleted_at TIMESTAMP;
+
+DROP INDEX monthly_charge;
+
+CREATE UNIQUE INDEX monthly_charge ON sanctions (user_id, (sanction_info->>'associated_rule'), charged_month)
+WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+DROP TABLE audit_events;
+
+DROP FUNCTION forbid_audit_events_changes();
@@ -0,0 +1,23 @@
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    actor_kind VARCHAR NOT NULL,
+    actor_id UUID,
+    entity_type VARCHAR NOT NULL,
+    entity_id UUID NOT NULL,
+    action VARCHAR NOT NULL,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMP NOT NULL default CURRENT_TIMESTAMP
+);
+
+CREATE INDEX audit_events_team_id ON audit_events (team_id, created_at);
+
+CREATE FUNCTION forbid_audit_events_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER append_only BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE PROCEDURE forbid_audit_events_changes();
@@ -0,0 +1,4 @@
+ALTER TABLE sanctions
+    DROP COLUMN appeal_status,
+    DROP COLUMN appeal_reason,
+    DROP COLUMN appeal_comment
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE sanctions
+    ADD COLUMN appeal_status VARCHAR,
+    ADD COLUMN appeal_reason VARCHAR,
+    ADD COLUMN appeal_comment VARCHAR
\ No newline at end of file
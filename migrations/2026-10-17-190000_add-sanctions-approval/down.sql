@@ -0,0 +1,8 @@
+DROP INDEX monthly_charge;
+
+ALTER TABLE sanctions
+    DROP COLUMN approval_status,
+    DROP COLUMN proposed_by;
+
+CREATE UNIQUE INDEX monthly_charge ON sanctions (user_id, (sanction_info->>'associated_rule'), charged_month)
+WHERE deleted_at IS NULL;
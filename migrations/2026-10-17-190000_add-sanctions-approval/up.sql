@@ -0,0 +1,9 @@
+ALTER TABLE sanctions
+    ADD COLUMN approval_status VARCHAR NOT NULL DEFAULT 'APPROVED',
+    ADD COLUMN proposed_by UUID,
+    ADD CONSTRAINT proposed_by FOREIGN KEY (proposed_by) REFERENCES users (id) ON DELETE SET NULL;
+
+DROP INDEX monthly_charge;
+
+CREATE UNIQUE INDEX monthly_charge ON sanctions (user_id, (sanction_info->>'associated_rule'), charged_month)
+WHERE deleted_at IS NULL AND approval_status = 'APPROVED';
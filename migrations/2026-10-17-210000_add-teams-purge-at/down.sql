@@ -0,0 +1 @@
+ALTER TABLE teams DROP COLUMN purge_at
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE teams ADD COLUMN purge_at TIMESTAMP
\ No newline at end of file
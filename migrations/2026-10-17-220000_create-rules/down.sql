@@ -0,0 +1,28 @@
+ALTER TABLE sanctions DROP COLUMN rule_id;
+
+ALTER TABLE teams ADD COLUMN rules JSONB[] NOT NULL DEFAULT '{}';
+
+UPDATE teams SET rules = ARRAY(
+    SELECT jsonb_build_object(
+        'id', rules.id,
+        'name', rules.name,
+        'category', rules.category,
+        'description', rules.description,
+        'kind', rules.kind,
+        'limits', rules.limits
+    )
+    FROM rules
+    WHERE rules.team_id = teams.id
+    ORDER BY rules.position
+);
+
+ALTER TABLE teams ALTER COLUMN rules DROP DEFAULT;
+
+CREATE OR REPLACE FUNCTION charged_month(UUID, TEXT, DATE) RETURNS DATE AS $$
+    SELECT DATE_TRUNC('month', $3::TIMESTAMP)::DATE
+    FROM teams, unnest(teams.rules) AS rules(rule)
+    WHERE teams.id = $1 AND rule->>'id' = $2 AND rule->'kind'->>'type' = 'MONTHLY'
+    LIMIT 1
+$$ LANGUAGE SQL;
+
+DROP TABLE rules;
@@ -0,0 +1,77 @@
+CREATE TABLE rules (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    name VARCHAR NOT NULL,
+    category VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    kind JSONB NOT NULL,
+    limits JSONB NOT NULL,
+    position INTEGER NOT NULL,
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE
+);
+
+CREATE INDEX rules_team_id ON rules (team_id, position);
+
+-- Rules used to be copied between teams with their ids, which are now global:
+-- the first copy keeps its id and the others get a new one.
+CREATE TEMPORARY TABLE team_rules AS
+SELECT
+    teams.id AS team_id,
+    (rule->>'id')::UUID AS original_id,
+    rule,
+    position,
+    ROW_NUMBER() OVER (PARTITION BY rule->>'id' ORDER BY teams.id, position) AS occurrence
+FROM teams, unnest(teams.rules) WITH ORDINALITY AS rules(rule, position);
+
+ALTER TABLE team_rules ADD COLUMN id UUID;
+
+UPDATE team_rules SET id = CASE
+    WHEN occurrence = 1 THEN original_id
+    ELSE md5(team_id::TEXT || original_id::TEXT || position::TEXT)::UUID
+END;
+
+INSERT INTO rules (id, team_id, name, category, description, kind, limits, position)
+SELECT
+    id,
+    team_id,
+    rule->>'name',
+    rule->>'category',
+    rule->>'description',
+    rule->'kind',
+    COALESCE(rule->'limits', '{}'),
+    position
+FROM team_rules;
+
+CREATE OR REPLACE FUNCTION charged_month(UUID, TEXT, DATE) RETURNS DATE AS $$
+    SELECT DATE_TRUNC('month', $3::TIMESTAMP)::DATE
+    FROM rules
+    WHERE rules.team_id = $1 AND rules.id::TEXT = $2 AND rules.kind->>'type' = 'MONTHLY'
+$$ LANGUAGE SQL;
+
+ALTER TABLE teams DROP COLUMN rules;
+
+ALTER TABLE sanctions ADD COLUMN rule_id UUID;
+
+-- The charged months are kept as they are while the rules are renamed.
+ALTER TABLE sanctions DISABLE TRIGGER charged_month;
+
+UPDATE sanctions SET
+    rule_id = team_rules.id,
+    sanction_info = jsonb_set(sanction_info, '{associated_rule}', to_jsonb(team_rules.id))
+FROM (
+    SELECT DISTINCT ON (team_id, original_id) team_id, original_id, id
+    FROM team_rules
+    ORDER BY team_id, original_id, position
+) AS team_rules
+WHERE team_rules.team_id = sanctions.team_id
+    AND team_rules.original_id = (sanctions.sanction_info->>'associated_rule')::UUID;
+
+ALTER TABLE sanctions ENABLE TRIGGER charged_month;
+
+DROP TABLE team_rules;
+
+ALTER TABLE sanctions
+    ADD CONSTRAINT rule_id FOREIGN KEY (rule_id) REFERENCES rules (id) ON DELETE SET NULL;
+
+CREATE INDEX sanctions_rule_id ON sanctions (rule_id);
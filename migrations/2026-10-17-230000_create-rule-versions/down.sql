@@ -0,0 +1,3 @@
+ALTER TABLE sanctions DROP COLUMN rule_version_id;
+
+DROP TABLE rule_versions;
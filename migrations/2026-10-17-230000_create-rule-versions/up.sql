@@ -0,0 +1,33 @@
+-- gen_random_uuid() is only built in from PostgreSQL 13.
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+CREATE TABLE rule_versions (
+    id UUID PRIMARY KEY,
+    rule_id UUID NOT NULL,
+    team_id UUID NOT NULL,
+    version INTEGER NOT NULL,
+    name VARCHAR NOT NULL,
+    category VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    kind JSONB NOT NULL,
+    limits JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE,
+    CONSTRAINT rule_version UNIQUE (rule_id, version)
+);
+
+INSERT INTO rule_versions (id, rule_id, team_id, version, name, category, description, kind, limits)
+SELECT gen_random_uuid(), id, team_id, 1, name, category, description, kind, limits
+FROM rules;
+
+ALTER TABLE sanctions ADD COLUMN rule_version_id UUID;
+
+UPDATE sanctions SET rule_version_id = rule_versions.id
+FROM rule_versions
+WHERE rule_versions.rule_id = sanctions.rule_id;
+
+ALTER TABLE sanctions
+    ADD CONSTRAINT rule_version_id FOREIGN KEY (rule_version_id) REFERENCES rule_versions (id);
+
+CREATE INDEX sanctions_rule_version_id ON sanctions (rule_version_id);
@@ -0,0 +1,5 @@
+ALTER TABLE sanctions
+    DROP CONSTRAINT rule_id,
+    ADD CONSTRAINT rule_id FOREIGN KEY (rule_id) REFERENCES rules (id) ON DELETE SET NULL;
+
+ALTER TABLE rules DROP COLUMN archived_at;
@@ -0,0 +1,5 @@
+ALTER TABLE rules ADD COLUMN archived_at TIMESTAMP;
+
+ALTER TABLE sanctions
+    DROP CONSTRAINT rule_id,
+    ADD CONSTRAINT rule_id FOREIGN KEY (rule_id) REFERENCES rules (id);
@@ -0,0 +1,23 @@
+DROP FUNCTION legacy_category(UUID);
+
+ALTER TABLE rule_versions
+    DROP CONSTRAINT category_id,
+    ADD COLUMN category VARCHAR NOT NULL DEFAULT 'TRAINING_DAY';
+
+UPDATE rule_versions SET category = 'GAME_DAY'
+FROM rule_categories
+WHERE rule_categories.id = rule_versions.category_id
+    AND rule_categories.name = 'Game day';
+
+ALTER TABLE rule_versions ALTER COLUMN category DROP DEFAULT, DROP COLUMN category_id;
+
+ALTER TABLE rules ADD COLUMN category VARCHAR NOT NULL DEFAULT 'TRAINING_DAY';
+
+UPDATE rules SET category = 'GAME_DAY'
+FROM rule_categories
+WHERE rule_categories.id = rules.category_id
+    AND rule_categories.name = 'Game day';
+
+ALTER TABLE rules ALTER COLUMN category DROP DEFAULT, DROP COLUMN category_id;
+
+DROP TABLE rule_categories;
@@ -0,0 +1,58 @@
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+CREATE TABLE rule_categories (
+    id UUID PRIMARY KEY,
+    team_id UUID NOT NULL,
+    name VARCHAR NOT NULL,
+    color VARCHAR NOT NULL,
+    position INTEGER NOT NULL,
+
+    CONSTRAINT team_id FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE,
+    CONSTRAINT category_name UNIQUE (team_id, name),
+    CONSTRAINT team_category UNIQUE (team_id, id)
+);
+
+INSERT INTO rule_categories (id, team_id, name, color, position)
+SELECT gen_random_uuid(), teams.id, defaults.name, defaults.color, defaults.position
+FROM teams, (VALUES
+    ('Game day', '#1E88E5', 0),
+    ('Training day', '#43A047', 1)
+) AS defaults(name, color, position);
+
+ALTER TABLE rules ADD COLUMN category_id UUID;
+
+UPDATE rules SET category_id = rule_categories.id
+FROM rule_categories
+WHERE rule_categories.team_id = rules.team_id
+    AND rule_categories.name = CASE rules.category
+        WHEN 'GAME_DAY' THEN 'Game day'
+        ELSE 'Training day'
+    END;
+
+ALTER TABLE rules
+    DROP COLUMN category,
+    ADD CONSTRAINT category_id FOREIGN KEY (team_id, category_id)
+        REFERENCES rule_categories (team_id, id);
+
+ALTER TABLE rule_versions ADD COLUMN category_id UUID;
+
+UPDATE rule_versions SET category_id = rule_categories.id
+FROM rule_categories
+WHERE rule_categories.team_id = rule_versions.team_id
+    AND rule_categories.name = CASE rule_versions.category
+        WHEN 'GAME_DAY' THEN 'Game day'
+        ELSE 'Training day'
+    END;
+
+ALTER TABLE rule_versions
+    DROP COLUMN category,
+    ADD CONSTRAINT category_id FOREIGN KEY (team_id, category_id)
+        REFERENCES rule_categories (team_id, id);
+
+-- Still serialized with the rules for the older clients.
+CREATE FUNCTION legacy_category(UUID) RETURNS VARCHAR AS $$
+    SELECT CASE
+        WHEN EXISTS (SELECT 1 FROM rule_categories WHERE id = $1 AND name = 'Game day') THEN 'GAME_DAY'
+        ELSE 'TRAINING_DAY'
+    END
+$$ LANGUAGE SQL STABLE;
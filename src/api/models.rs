@@ -3,7 +3,7 @@ use serde::Serialize;
 
 use crate::database::postgres::DbError;
 use crate::sanctions::{
-    models::SanctionInfoError,
+    models::{PriceError, SanctionInfoError},
     utils::parameters::{ParameterError, ParameterErrorKind},
 };
 
@@ -27,6 +27,27 @@ impl ErrorResponse {
             description,
         }
     }
+
+    pub fn not_valid(description: String) -> ErrorResponse {
+        ErrorResponse {
+            kind: ErrorKind::NotValid,
+            description,
+        }
+    }
+
+    pub fn unauthorized(description: String) -> ErrorResponse {
+        ErrorResponse {
+            kind: ErrorKind::Unauthorized,
+            description,
+        }
+    }
+
+    pub fn forbidden(description: String) -> ErrorResponse {
+        ErrorResponse {
+            kind: ErrorKind::Forbidden,
+            description,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -40,6 +61,8 @@ pub enum ErrorKind {
     DuplicatedField,
     BadParameter,
     NotValid,
+    Unauthorized,
+    Forbidden,
 }
 
 impl ErrorKind {
@@ -53,6 +76,8 @@ impl ErrorKind {
             ErrorKind::DuplicatedField => 400,
             ErrorKind::BadParameter => 400,
             ErrorKind::NotValid => 400,
+            ErrorKind::Unauthorized => 401,
+            ErrorKind::Forbidden => 403,
         }
     }
 }
@@ -102,6 +127,19 @@ impl From<SanctionInfoError> for ErrorResponse {
     }
 }
 
+impl From<PriceError> for ErrorResponse {
+    fn from(error: PriceError) -> Self {
+        match error {
+            PriceError::SanctionInfo(error) => error.into(),
+            PriceError::Overflow(rule_name) => ErrorResponse::not_valid(format!(
+                "The price of the rule {} is too high to be computed",
+                rule_name
+            )),
+            PriceError::Db(error) => error.into(),
+        }
+    }
+}
+
 impl From<ParameterError> for ErrorResponse {
     fn from(error: ParameterError) -> ErrorResponse {
         match error.kind {
@@ -149,23 +187,50 @@ pub mod test_utils {
             ("Content-Type".to_string(), "application/json".to_string())
         }
 
+        fn build(method: &str, url: String, token: Option<&str>, data: Vec<u8>) -> Request {
+            let mut headers = vec![RequestBuilder::json_header()];
+
+            if let Some(token) = token {
+                headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+            }
+
+            Request::fake_http(method, url, headers, data)
+        }
+
+        fn serialize(data: &Value) -> Vec<u8> {
+            serde_json::to_vec(data).expect("Failed to serialize data")
+        }
+
         pub fn get(url: String) -> Request {
-            Request::fake_http("GET", url, vec![RequestBuilder::json_header()], vec![])
+            RequestBuilder::build("GET", url, None, vec![])
         }
 
         pub fn post(url: String, data: &Value) -> Request {
-            let serialized_data = serde_json::to_vec(data).expect("Failed to serialize data");
+            RequestBuilder::build("POST", url, None, RequestBuilder::serialize(data))
+        }
 
-            Request::fake_http(
-                "POST",
-                url,
-                vec![RequestBuilder::json_header()],
-                serialized_data,
-            )
+        pub fn patch(url: String, data: &Value) -> Request {
+            RequestBuilder::build("PATCH", url, None, RequestBuilder::serialize(data))
         }
 
         pub fn delete(url: String) -> Request {
-            Request::fake_http("DELETE", url, vec![RequestBuilder::json_header()], vec![])
+            RequestBuilder::build("DELETE", url, None, vec![])
+        }
+
+        pub fn delete_with_data(url: String, data: &Value) -> Request {
+            RequestBuilder::build("DELETE", url, None, RequestBuilder::serialize(data))
+        }
+
+        pub fn get_authenticated(url: String, token: &str) -> Request {
+            RequestBuilder::build("GET", url, Some(token), vec![])
+        }
+
+        pub fn post_authenticated(url: String, data: &Value, token: &str) -> Request {
+            RequestBuilder::build("POST", url, Some(token), RequestBuilder::serialize(data))
+        }
+
+        pub fn delete_authenticated(url: String, token: &str) -> Request {
+            RequestBuilder::build("DELETE", url, Some(token), vec![])
         }
     }
 }
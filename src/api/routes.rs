@@ -1,11 +1,35 @@
 use super::models::ErrorResponse;
+use crate::audit::{
+    interface::AuditDb, models::Actor, routes::handle_request as audit_request_handling,
+    utils::recorder::AuditedDb,
+};
+use crate::database::postgres::DbError;
+use crate::payments::{interface::PaymentsDb, routes::handle_request as payments_request_handling};
 use crate::sanctions::{
-    interface::SanctionsDb, routes::handle_request as sanctions_request_handling,
+    interface::SanctionsDb,
+    routes::{
+        handle_actor_request as sanctions_actor_request_handling,
+        handle_request as sanctions_request_handling,
+    },
+};
+use crate::scheduler::{
+    interface::MonthlyRunsDb, routes::handle_request as scheduler_request_handling,
+};
+use crate::sessions::{
+    interface::SessionsDb,
+    models::{Scope, Session, SessionConfig},
+    routes::handle_request as sessions_request_handling,
+    utils::authorization::authenticate,
 };
 use crate::teams::{interface::TeamsDb, routes::handle_request as teams_request_handling};
-use crate::users::{interface::UsersDb, routes::handle_request as users_request_handling};
-use rouille::{find_route, Request, Response};
+use crate::users::{
+    interface::UsersDb,
+    models::{Permission, Role},
+    routes::handle_request as users_request_handling,
+};
+use rouille::{find_route, router, Request, Response};
 use serde::Serialize;
+use uuid::Uuid;
 
 fn extract_response<T>(result: Result<T, ErrorResponse>) -> Response
 where
@@ -17,16 +41,426 @@ where
     }
 }
 
-pub fn handle_request<T>(request: &Request, db: &T) -> Response
+fn extract_team_id(url: &str) -> Option<Uuid> {
+    let mut segments = url.trim_start_matches('/').split('/');
+
+    match (segments.next(), segments.next()) {
+        (Some("teams"), Some(team_id)) => Uuid::parse_str(team_id).ok(),
+        _ => None,
+    }
+}
+
+fn required_permission(request: &Request) -> Option<Permission> {
+    router!(request,
+        (POST) (/teams/{_team_id: Uuid}) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/rules) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/rules/{_rule_id: Uuid}) => {
+            Some(Permission::EditRules)
+        },
+        (DELETE) (/teams/{_team_id: Uuid}/rules/{_rule_id: Uuid}) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/rules/{_rule_id: Uuid}/archive) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/rules/{_rule_id: Uuid}/restore) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/categories) => {
+            Some(Permission::EditRules)
+        },
+        (POST) (/teams/{_team_id: Uuid}/categories/{_category_id: Uuid}) => {
+            Some(Permission::EditRules)
+        },
+        (DELETE) (/teams/{_team_id: Uuid}/categories/{_category_id: Uuid}) => {
+            Some(Permission::EditRules)
+        },
+        (GET) (/teams/{_team_id: Uuid}/export) => {
+            Some(Permission::ManageTeam)
+        },
+        (POST) (/teams/{_team_id: Uuid}/users) => {
+            Some(Permission::EditUsers)
+        },
+        (POST) (/teams/{_team_id: Uuid}/users/{_user_id: Uuid}) => {
+            Some(Permission::EditUsers)
+        },
+        (POST) (/teams/{_team_id: Uuid}/users/{_user_id: Uuid}/deactivate) => {
+            Some(Permission::EditUsers)
+        },
+        (POST) (/teams/{_team_id: Uuid}/users/{_user_id: Uuid}/reactivate) => {
+            Some(Permission::EditUsers)
+        },
+        (POST) (/teams/{_team_id: Uuid}/users/{_user_id: Uuid}/payments) => {
+            Some(Permission::RecordPayments)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions) => {
+            Some(Permission::CreateSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/monthly) => {
+            Some(Permission::CreateSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/proposals) => {
+            Some(Permission::ProposeSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}/approve) => {
+            Some(Permission::CreateSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}/reject) => {
+            Some(Permission::CreateSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}) => {
+            Some(Permission::CreateSanctions)
+        },
+        (PATCH) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}) => {
+            Some(Permission::CreateSanctions)
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}/contest) => {
+            // Only the sanctioned user can contest, which the route checks itself.
+            None
+        },
+        (POST) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}/restore) => {
+            Some(Permission::DeleteSanctions)
+        },
+        (DELETE) (/teams/{_team_id: Uuid}/sanctions/{_sanction_id: Uuid}) => {
+            Some(Permission::DeleteSanctions)
+        },
+        _ => match request.method() {
+            "GET" => None,
+            _ => Some(Permission::ManageTeam),
+        }
+    )
+}
+
+/// A team scheduled for deletion is frozen until it is restored: it can still
+/// be read and exported, but any other write is refused.
+fn check_not_frozen<T>(request: &Request, db: &T, team_id: Uuid) -> Result<(), ErrorResponse>
+where
+    T: TeamsDb,
+{
+    let allowed = router!(request,
+        (GET) (/teams/{_team_id: Uuid}/export) => { true },
+        (POST) (/teams/{_team_id: Uuid}/restore) => { true },
+        _ => request.method() == "GET"
+    );
+
+    if allowed {
+        return Ok(());
+    }
+
+    match db.get_team(team_id) {
+        Ok(team) if team.purge_at.is_some() => Err(ErrorResponse::forbidden(String::from(
+            "This team is scheduled for deletion and must be restored first",
+        ))),
+        Ok(_) | Err(DbError::NotFound) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Sessions opened with the admin password act as team admins, while sessions
+/// opened by a user get the role of this user at the time of the request.
+fn session_role<T>(db: &T, session: &Session) -> Result<Option<Role>, ErrorResponse>
+where
+    T: UsersDb,
+{
+    match (session.scope, session.user_id) {
+        (Scope::Admin, _) => Ok(Some(Role::Admin)),
+        (Scope::User, Some(user_id)) => Ok(Some(db.get_user(session.team_id, user_id)?.role)),
+        _ => Ok(None),
+    }
+}
+
+/// Every route under `/teams/{team_id}` requires a session bound to this team,
+/// and its role must be granted the permission required by the route. Returns
+/// the actor to which the writes of the request are attributed.
+fn authorize<T>(request: &Request, db: &T, config: &SessionConfig) -> Result<Actor, ErrorResponse>
+where
+    T: SessionsDb + TeamsDb + UsersDb,
+{
+    let team_id = match extract_team_id(&request.url()) {
+        Some(team_id) => team_id,
+        None => return Ok(Actor::anonymous()),
+    };
+
+    let session = authenticate(request, db, config)?;
+
+    if session.team_id != team_id {
+        return Err(ErrorResponse::unauthorized(String::from(
+            "The session isn't bound to this team",
+        )));
+    }
+
+    if let Some(permission) = required_permission(request) {
+        match session_role(db, &session)? {
+            Some(role) if role.can(permission) => {}
+            _ => {
+                return Err(ErrorResponse::forbidden(String::from(
+                    "This session isn't allowed to perform this action",
+                )))
+            }
+        }
+    }
+
+    check_not_frozen(request, db, team_id)?;
+
+    Ok(Actor::from(&session))
+}
+
+pub fn handle_request<T>(request: &Request, db: &T, config: &SessionConfig) -> Response
 where
-    T: TeamsDb + UsersDb + SanctionsDb,
+    T: TeamsDb + UsersDb + SanctionsDb + SessionsDb + PaymentsDb + MonthlyRunsDb + AuditDb,
 {
     match request.method() {
         "OPTIONS" => Response::empty_204(),
-        _ => find_route!(
-            extract_response(teams_request_handling(request, db)),
-            extract_response(users_request_handling(request, db)),
-            extract_response(sanctions_request_handling(request, db))
-        ),
+        _ => match authorize(request, db, config) {
+            Ok(actor) => {
+                let db = &AuditedDb::new(db, actor);
+
+                find_route!(
+                    extract_response(sessions_request_handling(request, db, config)),
+                    extract_response(teams_request_handling(request, db)),
+                    extract_response(users_request_handling(request, db)),
+                    extract_response(payments_request_handling(request, db)),
+                    extract_response(scheduler_request_handling(request, db)),
+                    extract_response(audit_request_handling(request, db)),
+                    extract_response(sanctions_actor_request_handling(request, db, actor)),
+                    extract_response(sanctions_request_handling(request, db))
+                )
+            }
+            Err(e) => e.into(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::models::test_utils::RequestBuilder;
+    use crate::sessions::utils::token::sign;
+    use crate::test_utils::routes::{session_config, DbMock, TeamsDbMock, UsersDbMock};
+
+    fn token(team_id: Uuid, scope: Scope) -> String {
+        sign(
+            &Session {
+                user_id: match scope {
+                    Scope::User => Some(Uuid::new_v4()),
+                    _ => None,
+                },
+                ..Session::new(team_id, scope, session_config().lifetime)
+            },
+            &session_config().secret,
+        )
+    }
+
+    #[test]
+    fn test_public_routes() {
+        let response = handle_request(
+            &RequestBuilder::post(String::from("/login"), &json!({ "name": "CHBC" })),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+
+        let response = handle_request(
+            &RequestBuilder::post(
+                String::from("/teams"),
+                &json!({ "name": "CHBC", "admin_password": "password", "rules": [] }),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+    }
+
+    #[test]
+    fn test_authorized_routes() {
+        let team_id = Uuid::new_v4();
+
+        let response = handle_request(
+            &RequestBuilder::get_authenticated(
+                format!("/teams/{}/users", team_id),
+                &token(team_id, Scope::ReadOnly),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+
+        let response = handle_request(
+            &RequestBuilder::delete_authenticated(
+                format!("/teams/{}/sanctions/{}", team_id, Uuid::new_v4()),
+                &token(team_id, Scope::Admin),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+    }
+
+    #[test]
+    fn test_unauthorized_routes() {
+        let team_id = Uuid::new_v4();
+
+        let response = handle_request(
+            &RequestBuilder::get(format!("/teams/{}", team_id)),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 401);
+
+        let response = handle_request(
+            &RequestBuilder::get_authenticated(
+                format!("/teams/{}", team_id),
+                &token(Uuid::new_v4(), Scope::Admin),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 401);
+
+        let response = handle_request(
+            &RequestBuilder::post_authenticated(
+                format!("/teams/{}", team_id),
+                &json!({ "name": "CHBC", "admin_password": "password", "rules": [] }),
+                &token(team_id, Scope::User),
+            ),
+            &DbMock {
+                users_db: UsersDbMock::NotFound,
+                ..Default::default()
+            },
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 401);
+    }
+
+    #[test]
+    fn test_forbidden_routes() {
+        let team_id = Uuid::new_v4();
+
+        let response = handle_request(
+            &RequestBuilder::delete_authenticated(
+                format!("/teams/{}/sanctions/{}", team_id, Uuid::new_v4()),
+                &token(team_id, Scope::ReadOnly),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 403);
+
+        let response = handle_request(
+            &RequestBuilder::delete_authenticated(
+                format!("/teams/{}/sanctions/{}", team_id, Uuid::new_v4()),
+                &token(team_id, Scope::User),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 403);
+    }
+
+    #[test]
+    fn test_team_pending_deletion_routes() {
+        let team_id = Uuid::new_v4();
+        let db = DbMock {
+            teams_db: TeamsDbMock::PendingDeletion,
+            ..Default::default()
+        };
+
+        let response = handle_request(
+            &RequestBuilder::post_authenticated(
+                format!("/teams/{}/users", team_id),
+                &json!({ "firstname": "John", "lastname": "Snow" }),
+                &token(team_id, Scope::Admin),
+            ),
+            &db,
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 403);
+
+        let response = handle_request(
+            &RequestBuilder::get_authenticated(
+                format!("/teams/{}/export", team_id),
+                &token(team_id, Scope::Admin),
+            ),
+            &db,
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+
+        let response = handle_request(
+            &RequestBuilder::post_authenticated(
+                format!("/teams/{}/restore", team_id),
+                &json!({}),
+                &token(team_id, Scope::Admin),
+            ),
+            &db,
+            &session_config(),
+        );
+
+        assert_eq!(response.status_code, 200);
+    }
+
+    #[test]
+    fn test_permissions_by_role() {
+        let team_id = Uuid::new_v4();
+        let user = json!({ "firstname": "John", "lastname": "Snow" });
+
+        let delete_sanction = || {
+            RequestBuilder::delete_authenticated(
+                format!("/teams/{}/sanctions/{}", team_id, Uuid::new_v4()),
+                &token(team_id, Scope::User),
+            )
+        };
+        let propose_sanctions = || {
+            RequestBuilder::post_authenticated(
+                format!("/teams/{}/sanctions/proposals", team_id),
+                &json!([]),
+                &token(team_id, Scope::User),
+            )
+        };
+        let create_user = || {
+            RequestBuilder::post_authenticated(
+                format!("/teams/{}/users", team_id),
+                &user,
+                &token(team_id, Scope::User),
+            )
+        };
+
+        for (role, can_delete_sanction, can_create_user) in &[
+            (Role::Member, false, false),
+            (Role::Treasurer, true, false),
+            (Role::Admin, true, true),
+        ] {
+            let db = DbMock {
+                users_db: UsersDbMock::SuccessWithRole(*role),
+                ..Default::default()
+            };
+
+            let response = handle_request(&delete_sanction(), &db, &session_config());
+            assert_eq!(response.status_code == 200, *can_delete_sanction);
+            assert_eq!(response.status_code == 403, !can_delete_sanction);
+
+            let response = handle_request(&propose_sanctions(), &db, &session_config());
+            assert_eq!(response.status_code, 200);
+
+            let response = handle_request(&create_user(), &db, &session_config());
+            assert_eq!(response.status_code == 403, !can_create_user);
+        }
     }
 }
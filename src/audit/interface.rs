@@ -0,0 +1,22 @@
+use uuid::Uuid;
+
+use super::models::{AuditEvent, AuditFilter};
+use crate::database::postgres::DbError;
+
+pub trait AuditDb {
+    fn get_audit_events(
+        &self,
+        team_id: Uuid,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEvent>, DbError>;
+
+    fn count_audit_events(&self, team_id: Uuid, filter: &AuditFilter) -> Result<i64, DbError>;
+
+    fn create_audit_events(&self, events: &[AuditEvent]) -> Result<Vec<AuditEvent>, DbError>;
+
+    /// Runs `f` in a transaction, so that a write is never stored without the
+    /// audit events describing it.
+    fn transaction<R, F>(&self, f: F) -> Result<R, DbError>
+    where
+        F: FnOnce() -> Result<R, DbError>;
+}
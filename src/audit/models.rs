@@ -0,0 +1,257 @@
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Utc,
+};
+use diesel::{sql_types::Varchar, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::database::schema::audit_events;
+use crate::sanctions::models::Sanction;
+use crate::sessions::models::{Scope, Session};
+use crate::teams::models::{RuleCategory, Team};
+use crate::users::models::User;
+use crate::varchar_enum;
+
+/// An append-only record of a write, with the state of the entity before and
+/// after it.
+#[derive(Debug, Clone, Queryable, Insertable, PartialEq, Serialize)]
+#[table_name = "audit_events"]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub actor_kind: ActorKind,
+    pub actor_id: Option<Uuid>,
+    pub entity_type: EntityType,
+    pub entity_id: Uuid,
+    pub action: AuditAction,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: NaiveDateTime,
+}
+
+impl AuditEvent {
+    pub fn new<E>(actor: Actor, action: AuditAction, before: Option<&E>, after: &E) -> AuditEvent
+    where
+        E: Auditable,
+    {
+        AuditEvent {
+            id: Uuid::new_v4(),
+            team_id: after.team_id(),
+            actor_kind: actor.kind,
+            actor_id: actor.id,
+            entity_type: E::ENTITY_TYPE,
+            entity_id: after.entity_id(),
+            action,
+            before: before.and_then(|before| serde_json::to_value(before).ok()),
+            after: serde_json::to_value(after).ok(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Records the removal of an entity, which has no state afterwards.
+    pub fn deletion<E>(actor: Actor, entity: &E) -> AuditEvent
+    where
+        E: Auditable,
+    {
+        AuditEvent {
+            after: None,
+            ..AuditEvent::new(actor, AuditAction::Delete, Some(entity), entity)
+        }
+    }
+}
+
+impl Default for AuditEvent {
+    fn default() -> AuditEvent {
+        AuditEvent::new(
+            Actor::anonymous(),
+            AuditAction::Create,
+            None,
+            &Team::default(),
+        )
+    }
+}
+
+/// The entities whose writes are recorded in the audit log.
+pub trait Auditable: Serialize {
+    const ENTITY_TYPE: EntityType;
+
+    fn team_id(&self) -> Uuid;
+
+    fn entity_id(&self) -> Uuid;
+}
+
+impl Auditable for Team {
+    const ENTITY_TYPE: EntityType = EntityType::Team;
+
+    fn team_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Auditable for RuleCategory {
+    const ENTITY_TYPE: EntityType = EntityType::RuleCategory;
+
+    fn team_id(&self) -> Uuid {
+        self.team_id
+    }
+
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Auditable for User {
+    const ENTITY_TYPE: EntityType = EntityType::User;
+
+    fn team_id(&self) -> Uuid {
+        self.team_id
+    }
+
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Auditable for Sanction {
+    const ENTITY_TYPE: EntityType = EntityType::Sanction;
+
+    fn team_id(&self) -> Uuid {
+        self.team_id
+    }
+
+    fn entity_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+/// Who performed a write: the user of the session, the team admin, the
+/// monthly scheduler, or nobody authenticated when creating a team.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Actor {
+    pub kind: ActorKind,
+    pub id: Option<Uuid>,
+}
+
+impl Actor {
+    pub fn anonymous() -> Actor {
+        Actor {
+            kind: ActorKind::Anonymous,
+            id: None,
+        }
+    }
+
+    pub fn scheduler() -> Actor {
+        Actor {
+            kind: ActorKind::Scheduler,
+            id: None,
+        }
+    }
+}
+
+impl From<&Session> for Actor {
+    fn from(session: &Session) -> Actor {
+        match session.scope {
+            Scope::Admin => Actor {
+                kind: ActorKind::Admin,
+                id: None,
+            },
+            Scope::User => Actor {
+                kind: ActorKind::User,
+                id: session.user_id,
+            },
+            Scope::ReadOnly => Actor::anonymous(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum ActorKind {
+    Admin,
+    User,
+    Scheduler,
+    Anonymous,
+}
+
+varchar_enum!(ActorKind);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum EntityType {
+    Team,
+    User,
+    Sanction,
+    RuleCategory,
+}
+
+impl EntityType {
+    pub const ALL: [EntityType; 4] = [
+        EntityType::Team,
+        EntityType::User,
+        EntityType::Sanction,
+        EntityType::RuleCategory,
+    ];
+}
+
+varchar_enum!(EntityType);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    /// A team deletion scheduled for a later purge.
+    ScheduleDeletion,
+    Deactivate,
+    Reactivate,
+    /// A sanction contested by its user.
+    Contest,
+    /// An appeal accepted or rejected by an admin.
+    Decide,
+    /// A proposed sanction approved or rejected.
+    Approve,
+    Reject,
+}
+
+varchar_enum!(AuditAction);
+
+/// Selects a page of the audit log of a team, from the most recent event.
+#[derive(Debug, PartialEq)]
+pub struct AuditFilter {
+    pub entity_type: Option<EntityType>,
+    pub entity_id: Option<Uuid>,
+    pub date_interval: (Option<NaiveDate>, Option<NaiveDate>),
+    pub page: i64,
+    pub page_size: i64,
+}
+
+impl Default for AuditFilter {
+    fn default() -> AuditFilter {
+        AuditFilter {
+            entity_type: None,
+            entity_id: None,
+            date_interval: (None, None),
+            page: 1,
+            page_size: 50,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub events: Vec<AuditEvent>,
+    pub page: i64,
+    pub page_size: i64,
+    pub total: i64,
+}
@@ -0,0 +1,175 @@
+use diesel::{pg::Pg, prelude::*};
+use std::ops::Deref;
+use uuid::Uuid;
+
+use super::{
+    interface::AuditDb,
+    models::{AuditEvent, AuditFilter},
+};
+use crate::database::{
+    postgres::{DbConnection, DbError},
+    schema::audit_events,
+};
+
+fn filtered_events<'a>(team_id: Uuid, filter: &AuditFilter) -> audit_events::BoxedQuery<'a, Pg> {
+    let mut query = audit_events::table
+        .filter(audit_events::team_id.eq(team_id))
+        .into_boxed();
+
+    if let Some(entity_type) = filter.entity_type {
+        query = query.filter(audit_events::entity_type.eq(entity_type));
+    }
+
+    if let Some(entity_id) = filter.entity_id {
+        query = query.filter(audit_events::entity_id.eq(entity_id));
+    }
+
+    if let (Some(from), _) = filter.date_interval {
+        query = query.filter(audit_events::created_at.ge(from.and_hms(0, 0, 0)));
+    }
+
+    if let (_, Some(to)) = filter.date_interval {
+        query = query.filter(audit_events::created_at.lt(to.succ().and_hms(0, 0, 0)));
+    }
+
+    query
+}
+
+impl AuditDb for DbConnection {
+    fn get_audit_events(
+        &self,
+        team_id: Uuid,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEvent>, DbError> {
+        let events: Vec<AuditEvent> = filtered_events(team_id, filter)
+            .order((audit_events::created_at.desc(), audit_events::id))
+            .limit(filter.page_size)
+            .offset((filter.page - 1) * filter.page_size)
+            .get_results(self.deref())?;
+
+        Ok(events)
+    }
+
+    fn count_audit_events(&self, team_id: Uuid, filter: &AuditFilter) -> Result<i64, DbError> {
+        let count: i64 = filtered_events(team_id, filter)
+            .count()
+            .get_result(self.deref())?;
+
+        Ok(count)
+    }
+
+    fn create_audit_events(&self, events: &[AuditEvent]) -> Result<Vec<AuditEvent>, DbError> {
+        let events: Vec<AuditEvent> = diesel::insert_into(audit_events::table)
+            .values(events)
+            .get_results(self.deref())?;
+
+        Ok(events)
+    }
+
+    fn transaction<R, F>(&self, f: F) -> Result<R, DbError>
+    where
+        F: FnOnce() -> Result<R, DbError>,
+    {
+        self.deref().transaction(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+    use diesel::result::Error;
+
+    use super::*;
+    use crate::audit::models::{Actor, AuditAction, EntityType};
+    use crate::sanctions::models::Sanction;
+    use crate::teams::models::Team;
+    use crate::test_utils::postgres::init_connection;
+
+    #[test]
+    fn test_audit_events() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team = Team::default();
+            let sanction = Sanction {
+                id: Uuid::new_v4(),
+                team_id: team.id,
+                ..Default::default()
+            };
+            let mut events = vec![
+                AuditEvent::new(Actor::anonymous(), AuditAction::Create, None, &team),
+                AuditEvent::new(Actor::scheduler(), AuditAction::Create, None, &sanction),
+                AuditEvent::new(
+                    Actor::scheduler(),
+                    AuditAction::Delete,
+                    Some(&sanction),
+                    &sanction,
+                ),
+            ];
+            events[0].created_at = NaiveDate::from_ymd(2019, 10, 5).and_hms(12, 0, 0);
+            events[1].created_at = events[0].created_at + Duration::days(1);
+            events[2].created_at = events[0].created_at + Duration::days(2);
+
+            conn.create_audit_events(&events).unwrap();
+
+            let filter = AuditFilter::default();
+            assert_eq!(conn.count_audit_events(team.id, &filter).unwrap(), 3);
+            assert_eq!(
+                conn.get_audit_events(team.id, &filter)
+                    .unwrap()
+                    .iter()
+                    .map(|event| event.id)
+                    .collect::<Vec<Uuid>>(),
+                vec![events[2].id, events[1].id, events[0].id]
+            );
+
+            let filter = AuditFilter {
+                entity_type: Some(EntityType::Sanction),
+                page: 2,
+                page_size: 1,
+                ..Default::default()
+            };
+            assert_eq!(conn.count_audit_events(team.id, &filter).unwrap(), 2);
+            assert_eq!(
+                conn.get_audit_events(team.id, &filter).unwrap()[0].id,
+                events[1].id
+            );
+
+            let filter = AuditFilter {
+                entity_id: Some(sanction.id),
+                date_interval: (None, Some(NaiveDate::from_ymd(2019, 10, 6))),
+                ..Default::default()
+            };
+            assert_eq!(
+                conn.get_audit_events(team.id, &filter).unwrap()[0].action,
+                AuditAction::Create
+            );
+
+            let filter = AuditFilter {
+                date_interval: (Some(NaiveDate::from_ymd(2019, 10, 8)), None),
+                ..Default::default()
+            };
+            assert_eq!(conn.count_audit_events(team.id, &filter).unwrap(), 0);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_audit_events_are_append_only() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let event = conn
+                .create_audit_events(&[AuditEvent::default()])
+                .unwrap()
+                .remove(0);
+
+            let result = diesel::delete(audit_events::table.find(event.id)).execute(conn.deref());
+
+            assert!(result.is_err());
+
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,70 @@
+use rouille::{router, Request};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{interface::AuditDb, models::AuditPage, utils::parameters::filter_from_request};
+use crate::api::models::ErrorResponse;
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum ResultWrapper {
+    AuditPage(AuditPage),
+}
+
+pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResultWrapper, ErrorResponse>
+where
+    T: AuditDb,
+{
+    router!(request,
+        (GET) (/teams/{team_id: Uuid}/audit) => {
+            let filter = filter_from_request(request)?;
+
+            let events = db.get_audit_events(team_id, &filter)?;
+            let total = db.count_audit_events(team_id, &filter)?;
+
+            Ok(ResultWrapper::AuditPage(AuditPage {
+                events,
+                page: filter.page,
+                page_size: filter.page_size,
+                total,
+            }))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
+    use crate::test_utils::routes::DbMock;
+
+    #[test]
+    fn test_get_audit_events() {
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/audit?page=2&page_size=20", team_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["events"][0]["team_id"], json!(team_id));
+        assert_eq!(response["events"][0]["action"], json!("CREATE"));
+        assert_eq!(response["page"], json!(2));
+        assert_eq!(response["page_size"], json!(20));
+        assert_eq!(response["total"], json!(1));
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/audit?page=0", team_id)),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadParameter);
+    }
+}
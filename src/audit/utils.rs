@@ -0,0 +1,822 @@
+pub mod parameters {
+    use chrono::NaiveDate;
+    use rouille::Request;
+    use serde_json::Value;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    use super::super::models::{AuditFilter, EntityType};
+    use crate::sanctions::utils::parameters::{ParameterError, ParameterErrorKind};
+
+    pub const MAX_PAGE_SIZE: i64 = 100;
+
+    fn parse<T: FromStr>(
+        request: &Request,
+        parameter_name: &str,
+        expected_type: &str,
+    ) -> Result<Option<T>, ParameterError> {
+        match request.get_param(parameter_name) {
+            Some(value) => value.parse::<T>().map(Some).map_err(|_| ParameterError {
+                parameter_name: String::from(parameter_name),
+                kind: ParameterErrorKind::UnvalidType {
+                    expected_type: String::from(expected_type),
+                },
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_in_range(
+        request: &Request,
+        parameter_name: &str,
+        (min, max): (i64, i64),
+    ) -> Result<Option<i64>, ParameterError> {
+        match parse::<i64>(request, parameter_name, "number")? {
+            Some(value) if value < min || value > max => Err(ParameterError {
+                parameter_name: String::from(parameter_name),
+                kind: ParameterErrorKind::UnvalidValue {
+                    parameter_value: value.to_string(),
+                    reason: format!("This value must be between {} and {}", min, max),
+                },
+            }),
+            value => Ok(value),
+        }
+    }
+
+    fn parse_entity_type(request: &Request) -> Result<Option<EntityType>, ParameterError> {
+        match request.get_param("entity_type") {
+            Some(value) => serde_json::from_value(Value::String(value.clone()))
+                .map(Some)
+                .map_err(|_| {
+                    let entity_types: Vec<String> = EntityType::ALL
+                        .iter()
+                        .filter_map(|entity_type| match serde_json::to_value(entity_type) {
+                            Ok(Value::String(name)) => Some(name),
+                            _ => None,
+                        })
+                        .collect();
+
+                    ParameterError {
+                        parameter_name: String::from("entity_type"),
+                        kind: ParameterErrorKind::UnvalidValue {
+                            parameter_value: value,
+                            reason: format!(
+                                "This value must be one of {}",
+                                entity_types.join(", ")
+                            ),
+                        },
+                    }
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the `entity_type`, `entity_id`, `from`, `to`, `page` and
+    /// `page_size` parameters, the dates being inclusive. No parameter name may
+    /// end with another one, since `get_param` would match both.
+    pub fn filter_from_request(request: &Request) -> Result<AuditFilter, ParameterError> {
+        let default_filter = AuditFilter::default();
+
+        Ok(AuditFilter {
+            entity_type: parse_entity_type(request)?,
+            entity_id: parse::<Uuid>(request, "entity_id", "uuid")?,
+            date_interval: (
+                parse::<NaiveDate>(request, "from", "date")?,
+                parse::<NaiveDate>(request, "to", "date")?,
+            ),
+            page: parse_in_range(request, "page", (1, i64::from(i32::MAX)))?
+                .unwrap_or(default_filter.page),
+            page_size: parse_in_range(request, "page_size", (1, MAX_PAGE_SIZE))?
+                .unwrap_or(default_filter.page_size),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::api::models::test_utils::RequestBuilder;
+
+        #[test]
+        fn test_filter_from_request() {
+            let entity_id = Uuid::new_v4();
+            let request = RequestBuilder::get(format!(
+                "/root_url?entity_type=SANCTION&entity_id={}&from=2019-10-01&page=2&page_size=10",
+                entity_id
+            ));
+
+            assert_eq!(
+                filter_from_request(&request).unwrap(),
+                AuditFilter {
+                    entity_type: Some(EntityType::Sanction),
+                    entity_id: Some(entity_id),
+                    date_interval: (Some(NaiveDate::from_ymd(2019, 10, 1)), None),
+                    page: 2,
+                    page_size: 10,
+                }
+            );
+
+            let request = RequestBuilder::get(String::from("/root_url"));
+
+            assert_eq!(
+                filter_from_request(&request).unwrap(),
+                AuditFilter::default()
+            );
+        }
+
+        #[test]
+        fn test_filter_from_request_fails() {
+            let request = RequestBuilder::get(String::from("/root_url?entity_type=PAYMENT"));
+
+            let error = filter_from_request(&request).unwrap_err();
+
+            assert_eq!(error.parameter_name, String::from("entity_type"));
+            assert_eq!(
+                error.kind,
+                ParameterErrorKind::UnvalidValue {
+                    parameter_value: String::from("PAYMENT"),
+                    reason: String::from(
+                        "This value must be one of TEAM, USER, SANCTION, RULE_CATEGORY"
+                    )
+                }
+            );
+
+            let request = RequestBuilder::get(String::from("/root_url?to=yesterday"));
+
+            let error = filter_from_request(&request).unwrap_err();
+
+            assert_eq!(
+                error.kind,
+                ParameterErrorKind::UnvalidType {
+                    expected_type: String::from("date")
+                }
+            );
+
+            let request = RequestBuilder::get(String::from("/root_url?page_size=500"));
+
+            let error = filter_from_request(&request).unwrap_err();
+
+            assert_eq!(
+                error.kind,
+                ParameterErrorKind::UnvalidValue {
+                    parameter_value: String::from("500"),
+                    reason: String::from("This value must be between 1 and 100")
+                }
+            );
+        }
+    }
+}
+
+pub mod recorder {
+    use chrono::naive::{NaiveDate, NaiveDateTime};
+    use uuid::Uuid;
+
+    use super::super::{
+        interface::AuditDb,
+        models::{Actor, AuditAction, AuditEvent, AuditFilter},
+    };
+    use crate::database::postgres::DbError;
+    use crate::payments::{
+        interface::PaymentsDb,
+        models::{Balance, CreatePayment, Payment},
+    };
+    use crate::sanctions::{
+        interface::SanctionsDb,
+        models::{
+            AppealStatus, ApprovalStatus, CreateSanction, Sanction, UpdateAppeal, UpdateApproval,
+            UpdateSanction,
+        },
+    };
+    use crate::scheduler::{interface::MonthlyRunsDb, models::MonthlyRun};
+    use crate::sessions::{interface::SessionsDb, models::Session};
+    use crate::teams::{
+        interface::TeamsDb,
+        models::{Rule, RuleCategory, RuleVersion, Team, UpdateCategory, UpdateTeam},
+    };
+    use crate::users::{
+        interface::UsersDb,
+        models::{UpdateUser, User},
+    };
+
+    /// Records an audit event on behalf of `actor` for every write done through
+    /// `TeamsDb`, `UsersDb` and `SanctionsDb`, the other calls being forwarded
+    /// as is.
+    pub struct AuditedDb<'a, T> {
+        db: &'a T,
+        actor: Actor,
+    }
+
+    impl<'a, T> AuditedDb<'a, T>
+    where
+        T: AuditDb,
+    {
+        pub fn new(db: &'a T, actor: Actor) -> AuditedDb<'a, T> {
+            AuditedDb { db, actor }
+        }
+
+        fn record<R, F>(&self, write: F) -> Result<R, DbError>
+        where
+            F: FnOnce() -> Result<(R, Vec<AuditEvent>), DbError>,
+        {
+            self.db.transaction(|| {
+                let (result, events) = write()?;
+
+                if !events.is_empty() {
+                    self.db.create_audit_events(&events)?;
+                }
+
+                Ok(result)
+            })
+        }
+    }
+
+    impl<'a, T> AuditedDb<'a, T>
+    where
+        T: TeamsDb + AuditDb,
+    {
+        /// Rules belong to their team, so editing one is recorded as an update
+        /// of the team.
+        fn record_rule<F>(&self, team_id: Uuid, write: F) -> Result<Rule, DbError>
+        where
+            F: FnOnce() -> Result<Rule, DbError>,
+        {
+            self.record(|| {
+                let before = self.db.get_team(team_id)?;
+                let rule = write()?;
+                let team = self.db.get_team(team_id)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Update, Some(&before), &team);
+
+                Ok((rule, vec![event]))
+            })
+        }
+    }
+
+    impl<'a, T> TeamsDb for AuditedDb<'a, T>
+    where
+        T: TeamsDb + AuditDb,
+    {
+        fn login(&self, name: &str, admin_password: &Option<String>) -> Result<Uuid, DbError> {
+            self.db.login(name, admin_password)
+        }
+
+        fn get_team_ids(&self) -> Result<Vec<Uuid>, DbError> {
+            self.db.get_team_ids()
+        }
+
+        fn get_team(&self, id: Uuid) -> Result<Team, DbError> {
+            self.db.get_team(id)
+        }
+
+        fn create_team(&self, team: &Team) -> Result<Team, DbError> {
+            self.record(|| {
+                let team = self.db.create_team(team)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Create, None, &team);
+
+                Ok((team, vec![event]))
+            })
+        }
+
+        fn update_team(&self, id: Uuid, team: &UpdateTeam) -> Result<Team, DbError> {
+            self.record(|| {
+                let before = self.db.get_team(id)?;
+                let team = self.db.update_team(id, team)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Update, Some(&before), &team);
+
+                Ok((team, vec![event]))
+            })
+        }
+
+        fn create_rule(&self, team_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+            self.record_rule(team_id, || self.db.create_rule(team_id, rule))
+        }
+
+        fn update_rule(&self, team_id: Uuid, rule_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+            self.record_rule(team_id, || self.db.update_rule(team_id, rule_id, rule))
+        }
+
+        fn delete_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+            self.record_rule(team_id, || self.db.delete_rule(team_id, rule_id))
+        }
+
+        fn archive_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+            self.record_rule(team_id, || self.db.archive_rule(team_id, rule_id))
+        }
+
+        fn restore_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+            self.record_rule(team_id, || self.db.restore_rule(team_id, rule_id))
+        }
+
+        fn get_rule_versions(
+            &self,
+            team_id: Uuid,
+            rule_id: Uuid,
+        ) -> Result<Vec<RuleVersion>, DbError> {
+            self.db.get_rule_versions(team_id, rule_id)
+        }
+
+        fn get_rule_version(
+            &self,
+            team_id: Uuid,
+            version_id: Uuid,
+        ) -> Result<RuleVersion, DbError> {
+            self.db.get_rule_version(team_id, version_id)
+        }
+
+        fn get_categories(&self, team_id: Uuid) -> Result<Vec<RuleCategory>, DbError> {
+            self.db.get_categories(team_id)
+        }
+
+        fn create_category(&self, category: &RuleCategory) -> Result<RuleCategory, DbError> {
+            self.record(|| {
+                let category = self.db.create_category(category)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Create, None, &category);
+
+                Ok((category, vec![event]))
+            })
+        }
+
+        fn update_category(
+            &self,
+            team_id: Uuid,
+            category_id: Uuid,
+            category: &UpdateCategory,
+        ) -> Result<RuleCategory, DbError> {
+            self.record(|| {
+                let before = self
+                    .db
+                    .get_categories(team_id)?
+                    .into_iter()
+                    .find(|before| before.id == category_id)
+                    .ok_or(DbError::NotFound)?;
+                let category = self.db.update_category(team_id, category_id, category)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Update, Some(&before), &category);
+
+                Ok((category, vec![event]))
+            })
+        }
+
+        fn delete_category(
+            &self,
+            team_id: Uuid,
+            category_id: Uuid,
+        ) -> Result<RuleCategory, DbError> {
+            self.record(|| {
+                let category = self.db.delete_category(team_id, category_id)?;
+                let event = AuditEvent::deletion(self.actor, &category);
+
+                Ok((category, vec![event]))
+            })
+        }
+
+        fn delete_team(&self, id: Uuid) -> Result<Team, DbError> {
+            self.record(|| {
+                let team = self.db.delete_team(id)?;
+                let event = AuditEvent::deletion(self.actor, &team);
+
+                Ok((team, vec![event]))
+            })
+        }
+
+        fn schedule_team_deletion(
+            &self,
+            id: Uuid,
+            purge_at: NaiveDateTime,
+        ) -> Result<Team, DbError> {
+            self.record(|| {
+                let before = self.db.get_team(id)?;
+                let team = self.db.schedule_team_deletion(id, purge_at)?;
+                let event = AuditEvent::new(
+                    self.actor,
+                    AuditAction::ScheduleDeletion,
+                    Some(&before),
+                    &team,
+                );
+
+                Ok((team, vec![event]))
+            })
+        }
+
+        fn restore_team(&self, id: Uuid) -> Result<Team, DbError> {
+            self.record(|| {
+                let before = self.db.get_team(id)?;
+                let team = self.db.restore_team(id)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Restore, Some(&before), &team);
+
+                Ok((team, vec![event]))
+            })
+        }
+
+        fn purge_teams(&self) -> Result<Vec<Team>, DbError> {
+            self.record(|| {
+                let teams = self.db.purge_teams()?;
+                let events = teams
+                    .iter()
+                    .map(|team| AuditEvent::deletion(self.actor, team))
+                    .collect();
+
+                Ok((teams, events))
+            })
+        }
+    }
+
+    impl<'a, T> UsersDb for AuditedDb<'a, T>
+    where
+        T: UsersDb + AuditDb,
+    {
+        fn get_users(
+            &self,
+            team_id: Uuid,
+            include_deactivated: bool,
+        ) -> Result<Vec<User>, DbError> {
+            self.db.get_users(team_id, include_deactivated)
+        }
+
+        fn get_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+            self.db.get_user(team_id, user_id)
+        }
+
+        fn create_user(&self, user: &User) -> Result<User, DbError> {
+            self.record(|| {
+                let user = self.db.create_user(user)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Create, None, &user);
+
+                Ok((user, vec![event]))
+            })
+        }
+
+        fn update_user(
+            &self,
+            team_id: Uuid,
+            user_id: Uuid,
+            user: &UpdateUser,
+        ) -> Result<User, DbError> {
+            self.record(|| {
+                let before = self.db.get_user(team_id, user_id)?;
+                let user = self.db.update_user(team_id, user_id, user)?;
+                let event = AuditEvent::new(self.actor, AuditAction::Update, Some(&before), &user);
+
+                Ok((user, vec![event]))
+            })
+        }
+
+        fn deactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+            self.record(|| {
+                let before = self.db.get_user(team_id, user_id)?;
+                let user = self.db.deactivate_user(team_id, user_id)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Deactivate, Some(&before), &user);
+
+                Ok((user, vec![event]))
+            })
+        }
+
+        fn reactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+            self.record(|| {
+                let before = self.db.get_user(team_id, user_id)?;
+                let user = self.db.reactivate_user(team_id, user_id)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Reactivate, Some(&before), &user);
+
+                Ok((user, vec![event]))
+            })
+        }
+
+        fn delete_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+            self.record(|| {
+                let user = self.db.delete_user(team_id, user_id)?;
+                let event = AuditEvent::deletion(self.actor, &user);
+
+                Ok((user, vec![event]))
+            })
+        }
+
+        fn login_user(&self, team_id: Uuid, email: &str, password: &str) -> Result<User, DbError> {
+            self.db.login_user(team_id, email, password)
+        }
+    }
+
+    impl<'a, T> SanctionsDb for AuditedDb<'a, T>
+    where
+        T: SanctionsDb + AuditDb,
+    {
+        fn get_sanctions(
+            &self,
+            team_id: Uuid,
+            date_interval: Option<(NaiveDate, NaiveDate)>,
+            include_deleted: bool,
+        ) -> Result<Vec<Sanction>, DbError> {
+            self.db
+                .get_sanctions(team_id, date_interval, include_deleted)
+        }
+
+        fn get_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+            self.db.get_sanction(team_id, sanction_id)
+        }
+
+        fn create_sanctions(&self, sanctions: &[CreateSanction]) -> Result<Vec<Sanction>, DbError> {
+            self.record(|| {
+                let sanctions = self.db.create_sanctions(sanctions)?;
+                let events = sanctions
+                    .iter()
+                    .map(|sanction| {
+                        AuditEvent::new(self.actor, AuditAction::Create, None, sanction)
+                    })
+                    .collect();
+
+                Ok((sanctions, events))
+            })
+        }
+
+        fn update_sanction(
+            &self,
+            team_id: Uuid,
+            sanction_id: Uuid,
+            sanction: &UpdateSanction,
+        ) -> Result<Sanction, DbError> {
+            self.record(|| {
+                let before = self.db.get_sanction(team_id, sanction_id)?;
+                let sanction = self.db.update_sanction(team_id, sanction_id, sanction)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Update, Some(&before), &sanction);
+
+                Ok((sanction, vec![event]))
+            })
+        }
+
+        fn update_appeal(
+            &self,
+            team_id: Uuid,
+            sanction_id: Uuid,
+            appeal: &UpdateAppeal,
+        ) -> Result<Sanction, DbError> {
+            self.record(|| {
+                let before = self.db.get_sanction(team_id, sanction_id)?;
+                let sanction = self.db.update_appeal(team_id, sanction_id, appeal)?;
+                let action = match appeal.appeal_status {
+                    AppealStatus::Contested => AuditAction::Contest,
+                    _ => AuditAction::Decide,
+                };
+                let event = AuditEvent::new(self.actor, action, Some(&before), &sanction);
+
+                Ok((sanction, vec![event]))
+            })
+        }
+
+        fn update_approval(
+            &self,
+            team_id: Uuid,
+            sanction_id: Uuid,
+            approval: &UpdateApproval,
+        ) -> Result<Sanction, DbError> {
+            self.record(|| {
+                let before = self.db.get_sanction(team_id, sanction_id)?;
+                let sanction = self.db.update_approval(team_id, sanction_id, approval)?;
+                let action = match approval.approval_status {
+                    ApprovalStatus::Rejected => AuditAction::Reject,
+                    _ => AuditAction::Approve,
+                };
+                let event = AuditEvent::new(self.actor, action, Some(&before), &sanction);
+
+                Ok((sanction, vec![event]))
+            })
+        }
+
+        fn delete_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+            self.record(|| {
+                let before = self.db.get_sanction(team_id, sanction_id)?;
+                let sanction = self.db.delete_sanction(team_id, sanction_id)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Delete, Some(&before), &sanction);
+
+                Ok((sanction, vec![event]))
+            })
+        }
+
+        fn restore_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+            self.record(|| {
+                let before = self
+                    .db
+                    .get_sanctions(team_id, None, true)?
+                    .into_iter()
+                    .find(|sanction| sanction.id == sanction_id);
+                let sanction = self.db.restore_sanction(team_id, sanction_id)?;
+                let event =
+                    AuditEvent::new(self.actor, AuditAction::Restore, before.as_ref(), &sanction);
+
+                Ok((sanction, vec![event]))
+            })
+        }
+    }
+
+    impl<'a, T> SessionsDb for AuditedDb<'a, T>
+    where
+        T: SessionsDb,
+    {
+        fn get_session(&self, id: Uuid) -> Result<Session, DbError> {
+            self.db.get_session(id)
+        }
+
+        fn create_session(&self, session: &Session) -> Result<Session, DbError> {
+            self.db.create_session(session)
+        }
+
+        fn delete_session(&self, id: Uuid) -> Result<Session, DbError> {
+            self.db.delete_session(id)
+        }
+    }
+
+    impl<'a, T> PaymentsDb for AuditedDb<'a, T>
+    where
+        T: PaymentsDb,
+    {
+        fn get_payments(
+            &self,
+            team_id: Uuid,
+            user_id: Option<Uuid>,
+        ) -> Result<Vec<Payment>, DbError> {
+            self.db.get_payments(team_id, user_id)
+        }
+
+        fn create_payments(&self, payments: &[CreatePayment]) -> Result<Vec<Payment>, DbError> {
+            self.db.create_payments(payments)
+        }
+
+        fn get_balances(
+            &self,
+            team_id: Uuid,
+            user_id: Option<Uuid>,
+            date_interval: Option<(NaiveDate, NaiveDate)>,
+        ) -> Result<Vec<Balance>, DbError> {
+            self.db.get_balances(team_id, user_id, date_interval)
+        }
+    }
+
+    impl<'a, T> MonthlyRunsDb for AuditedDb<'a, T>
+    where
+        T: MonthlyRunsDb,
+    {
+        fn get_monthly_runs(&self, team_id: Uuid) -> Result<Vec<MonthlyRun>, DbError> {
+            self.db.get_monthly_runs(team_id)
+        }
+
+        fn create_monthly_run(&self, run: &MonthlyRun) -> Result<MonthlyRun, DbError> {
+            self.db.create_monthly_run(run)
+        }
+    }
+
+    impl<'a, T> AuditDb for AuditedDb<'a, T>
+    where
+        T: AuditDb,
+    {
+        fn get_audit_events(
+            &self,
+            team_id: Uuid,
+            filter: &AuditFilter,
+        ) -> Result<Vec<AuditEvent>, DbError> {
+            self.db.get_audit_events(team_id, filter)
+        }
+
+        fn count_audit_events(&self, team_id: Uuid, filter: &AuditFilter) -> Result<i64, DbError> {
+            self.db.count_audit_events(team_id, filter)
+        }
+
+        fn create_audit_events(&self, events: &[AuditEvent]) -> Result<Vec<AuditEvent>, DbError> {
+            self.db.create_audit_events(events)
+        }
+
+        fn transaction<R, F>(&self, f: F) -> Result<R, DbError>
+        where
+            F: FnOnce() -> Result<R, DbError>,
+        {
+            self.db.transaction(f)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::Utc;
+        use diesel::{result::Error, Connection};
+        use serde_json::json;
+        use std::ops::Deref;
+
+        use super::*;
+        use crate::audit::models::{ActorKind, EntityType};
+        use crate::test_utils::postgres::{create_team_with_rule, init_connection};
+
+        #[test]
+        fn test_audited_db_records_writes() {
+            let conn = init_connection();
+
+            conn.deref().test_transaction::<_, Error, _>(|| {
+                let user_id = Uuid::new_v4();
+                let db = AuditedDb::new(
+                    &conn,
+                    Actor {
+                        kind: ActorKind::User,
+                        id: Some(user_id),
+                    },
+                );
+
+                let team_id = create_team_with_rule(&db);
+                db.create_user(&User {
+                    id: user_id,
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap();
+                let sanction = db
+                    .create_sanctions(&[CreateSanction {
+                        id: Uuid::new_v4(),
+                        user_id,
+                        team_id,
+                        ..Default::default()
+                    }])
+                    .unwrap()
+                    .remove(0);
+                db.delete_sanction(team_id, sanction.id).unwrap();
+                db.restore_sanction(team_id, sanction.id).unwrap();
+
+                let events = conn
+                    .get_audit_events(team_id, &AuditFilter::default())
+                    .unwrap();
+
+                assert_eq!(events.len(), 5);
+                assert!(events.iter().all(|event| event.actor_id == Some(user_id)));
+
+                let events = conn
+                    .get_audit_events(
+                        team_id,
+                        &AuditFilter {
+                            entity_id: Some(sanction.id),
+                            ..Default::default()
+                        },
+                    )
+                    .unwrap();
+                let deletion = events
+                    .iter()
+                    .find(|event| event.action == AuditAction::Delete)
+                    .unwrap();
+
+                assert_eq!(events.len(), 3);
+                assert_eq!(deletion.entity_type, EntityType::Sanction);
+                assert_eq!(deletion.before.as_ref().unwrap()["deleted_at"], json!(null));
+                assert!(!deletion.after.as_ref().unwrap()["deleted_at"].is_null());
+
+                Ok(())
+            });
+        }
+
+        #[test]
+        fn test_audited_db_records_deletions() {
+            let conn = init_connection();
+
+            conn.deref().test_transaction::<_, Error, _>(|| {
+                let team_id = conn.create_team(&Team::default()).unwrap().id;
+                let db = AuditedDb::new(&conn, Actor::anonymous());
+                let user = db
+                    .create_user(&User {
+                        team_id,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                db.schedule_team_deletion(team_id, Utc::now().naive_utc())
+                    .unwrap();
+                db.delete_user(team_id, user.id).unwrap();
+
+                let events = conn
+                    .get_audit_events(team_id, &AuditFilter::default())
+                    .unwrap();
+
+                assert_eq!(events.len(), 3);
+                assert_eq!(events[0].action, AuditAction::Delete);
+                assert!(events[0].before.is_some());
+                assert_eq!(events[0].after, None);
+                assert_eq!(events[1].action, AuditAction::ScheduleDeletion);
+                assert!(events[1].after.is_some());
+
+                Ok(())
+            });
+        }
+
+        #[test]
+        fn test_audited_db_records_nothing_on_failure() {
+            let conn = init_connection();
+
+            conn.deref().test_transaction::<_, Error, _>(|| {
+                let team_id = conn.create_team(&Team::default()).unwrap().id;
+                let db = AuditedDb::new(&conn, Actor::anonymous());
+
+                let error = db.delete_sanction(team_id, Uuid::new_v4()).unwrap_err();
+
+                assert_eq!(error, DbError::NotFound);
+                assert_eq!(
+                    conn.count_audit_events(team_id, &AuditFilter::default())
+                        .unwrap(),
+                    0
+                );
+
+                Ok(())
+            });
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use diesel::pg::PgConnection;
 use r2d2_diesel::ConnectionManager;
 use std::ops::Deref;
+use std::time::Duration;
 
 #[derive(Debug, PartialEq)]
 pub enum DbError {
@@ -17,6 +18,12 @@ impl From<r2d2::Error> for DbError {
     }
 }
 
+impl From<bcrypt::BcryptError> for DbError {
+    fn from(_: bcrypt::BcryptError) -> DbError {
+        DbError::Unknown
+    }
+}
+
 impl From<diesel::result::Error> for DbError {
     fn from(error: diesel::result::Error) -> DbError {
         match error {
@@ -46,9 +53,45 @@ impl From<diesel::result::Error> for DbError {
     }
 }
 
-pub fn init_db_connection(database_url: &str) -> Result<DbConnection, DbError> {
+pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub connection_timeout: Duration,
+    pub idle_timeout: Option<Duration>,
+    pub max_lifetime: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> PoolConfig {
+        PoolConfig {
+            max_size: 10,
+            min_idle: None,
+            connection_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+        }
+    }
+}
+
+/// Builds the pool without opening any connection, so that an unreachable
+/// database surfaces as `DbError::ServiceUnavailable` on each request instead
+/// of preventing the server from starting.
+pub fn init_db_pool(database_url: &str, config: &PoolConfig) -> DbPool {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool = r2d2::Pool::new(manager)?;
+
+    r2d2::Pool::builder()
+        .max_size(config.max_size)
+        .min_idle(config.min_idle)
+        .connection_timeout(config.connection_timeout)
+        .idle_timeout(config.idle_timeout)
+        .max_lifetime(config.max_lifetime)
+        .build_unchecked(manager)
+}
+
+pub fn get_db_connection(pool: &DbPool) -> Result<DbConnection, DbError> {
     let connection = pool.get()?;
     Ok(DbConnection(connection))
 }
@@ -62,3 +105,38 @@ impl Deref for DbConnection {
         &self.0
     }
 }
+
+/// Stores an enum deriving `Serialize`, `Deserialize`, `AsExpression` and
+/// `FromSqlRow` (with `#[sql_type = "Varchar"]`) as its serialized name.
+#[macro_export]
+macro_rules! varchar_enum {
+    ($name:ident) => {
+        impl diesel::deserialize::FromSql<diesel::sql_types::Varchar, diesel::pg::Pg> for $name {
+            fn from_sql(bytes: Option<&[u8]>) -> diesel::deserialize::Result<Self> {
+                let value: String = diesel::deserialize::FromSql::<
+                    diesel::sql_types::Varchar,
+                    diesel::pg::Pg,
+                >::from_sql(bytes)?;
+
+                serde_json::from_value(serde_json::Value::String(value)).map_err(Into::into)
+            }
+        }
+
+        impl diesel::serialize::ToSql<diesel::sql_types::Varchar, diesel::pg::Pg> for $name {
+            fn to_sql<W: std::io::Write>(
+                &self,
+                out: &mut diesel::serialize::Output<W, diesel::pg::Pg>,
+            ) -> diesel::serialize::Result {
+                match serde_json::to_value(self)? {
+                    serde_json::Value::String(value) => diesel::serialize::ToSql::<
+                        diesel::sql_types::Varchar,
+                        diesel::pg::Pg,
+                    >::to_sql(&value, out),
+                    _ => {
+                        Err(format!("{} must be serialized as a string", stringify!($name)).into())
+                    }
+                }
+            }
+        }
+    };
+}
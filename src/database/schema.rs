@@ -1,11 +1,105 @@
+table! {
+    audit_events (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        actor_kind -> Varchar,
+        actor_id -> Nullable<Uuid>,
+        entity_type -> Varchar,
+        entity_id -> Uuid,
+        action -> Varchar,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    monthly_runs (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        month -> Date,
+        ran_at -> Timestamp,
+    }
+}
+
+table! {
+    payments (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        user_id -> Uuid,
+        sanction_id -> Uuid,
+        amount -> BigInt,
+        created_at -> Date,
+    }
+}
+
+table! {
+    rule_categories (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        name -> Varchar,
+        color -> Varchar,
+        position -> Int4,
+    }
+}
+
+table! {
+    rule_versions (id) {
+        id -> Uuid,
+        rule_id -> Uuid,
+        team_id -> Uuid,
+        version -> Int4,
+        name -> Varchar,
+        description -> Varchar,
+        kind -> Jsonb,
+        limits -> Jsonb,
+        created_at -> Timestamp,
+        category_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    rules (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        name -> Varchar,
+        description -> Varchar,
+        kind -> Jsonb,
+        limits -> Jsonb,
+        position -> Int4,
+        archived_at -> Nullable<Timestamp>,
+        category_id -> Nullable<Uuid>,
+    }
+}
+
 table! {
     sanctions (id) {
         id -> Uuid,
         user_id -> Uuid,
         team_id -> Uuid,
         sanction_info -> Jsonb,
-        price -> Float4,
+        price -> BigInt,
         created_at -> Date,
+        charged_month -> Nullable<Date>,
+        applied_limit -> Nullable<Varchar>,
+        deleted_at -> Nullable<Timestamp>,
+        appeal_status -> Nullable<Varchar>,
+        appeal_reason -> Nullable<Varchar>,
+        appeal_comment -> Nullable<Varchar>,
+        approval_status -> Varchar,
+        proposed_by -> Nullable<Uuid>,
+        rule_id -> Nullable<Uuid>,
+        rule_version_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    sessions (id) {
+        id -> Uuid,
+        team_id -> Uuid,
+        scope -> Varchar,
+        expires_at -> Timestamp,
+        user_id -> Nullable<Uuid>,
     }
 }
 
@@ -14,7 +108,9 @@ table! {
         id -> Uuid,
         name -> Varchar,
         admin_password -> Varchar,
-        rules -> Array<Jsonb>,
+        currency -> Varchar,
+        locale -> Varchar,
+        purge_at -> Nullable<Timestamp>,
     }
 }
 
@@ -26,9 +122,32 @@ table! {
         lastname -> Varchar,
         nickname -> Nullable<Varchar>,
         email -> Nullable<Varchar>,
+        role -> Varchar,
+        password -> Nullable<Varchar>,
+        deactivated_at -> Nullable<Timestamp>,
     }
 }
 
+joinable!(monthly_runs -> teams (team_id));
+joinable!(payments -> sanctions (sanction_id));
+joinable!(rule_categories -> teams (team_id));
+joinable!(rule_versions -> teams (team_id));
+joinable!(rules -> teams (team_id));
+joinable!(sanctions -> rule_versions (rule_version_id));
+joinable!(sanctions -> rules (rule_id));
 joinable!(sanctions -> users (user_id));
+joinable!(sessions -> teams (team_id));
+joinable!(sessions -> users (user_id));
 
-allow_tables_to_appear_in_same_query!(sanctions, teams, users,);
+allow_tables_to_appear_in_same_query!(
+    audit_events,
+    monthly_runs,
+    payments,
+    rule_categories,
+    rule_versions,
+    rules,
+    sanctions,
+    sessions,
+    teams,
+    users,
+);
@@ -1,6 +1,10 @@
 pub mod api;
+pub mod audit;
 pub mod database;
+pub mod payments;
 pub mod sanctions;
+pub mod scheduler;
+pub mod sessions;
 pub mod teams;
 pub mod test_utils;
 pub mod users;
@@ -1,8 +1,19 @@
+use chrono::Local;
+use env_logger::Env;
+use log::{error, info};
 use rouille::{start_server, Response};
 use std::env::{var, VarError};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 use caisse_noire::api::{models::ErrorResponse, routes::handle_request};
-use caisse_noire::database::postgres::init_db_connection;
+use caisse_noire::audit::{models::Actor, utils::recorder::AuditedDb};
+use caisse_noire::database::postgres::{get_db_connection, init_db_pool, DbPool, PoolConfig};
+use caisse_noire::sanctions::models::PriceError;
+use caisse_noire::scheduler::utils::runner::run_missing_months;
+use caisse_noire::sessions::models::SessionConfig;
+use caisse_noire::teams::interface::TeamsDb;
 
 fn extract_var(var_name: &str) -> Result<String, VarError> {
     use dotenv::dotenv;
@@ -15,13 +26,100 @@ fn with_cors(response: Response) -> Response {
     match extract_var("ENABLED_ORIGIN").ok() {
         Some(origin) => response
             .with_additional_header("Access-Control-Allow-Origin", origin)
-            .with_additional_header("Access-Control-Allow-Headers", "content-type")
-            .with_additional_header("Access-Control-Allow-Methods", "GET, POST, DELETE"),
+            .with_additional_header(
+                "Access-Control-Allow-Headers",
+                "content-type, authorization",
+            )
+            .with_additional_header("Access-Control-Allow-Methods", "GET, POST, PATCH, DELETE"),
         None => response,
     }
 }
 
+fn extract_optional_number<T: FromStr>(var_name: &str) -> Option<T> {
+    extract_var(var_name)
+        .ok()
+        .map(|value| match value.parse::<T>() {
+            Ok(number) => number,
+            _ => panic!("{} must be a positive number", var_name),
+        })
+}
+
+fn pool_config() -> PoolConfig {
+    let default_config = PoolConfig::default();
+
+    PoolConfig {
+        max_size: extract_optional_number("DATABASE_POOL_MAX_SIZE")
+            .unwrap_or(default_config.max_size),
+        min_idle: extract_optional_number("DATABASE_POOL_MIN_IDLE").or(default_config.min_idle),
+        connection_timeout: extract_optional_number("DATABASE_POOL_CONNECTION_TIMEOUT")
+            .map(Duration::from_secs)
+            .unwrap_or(default_config.connection_timeout),
+        idle_timeout: extract_optional_number("DATABASE_POOL_IDLE_TIMEOUT")
+            .map(Duration::from_secs)
+            .or(default_config.idle_timeout),
+        max_lifetime: extract_optional_number("DATABASE_POOL_MAX_LIFETIME")
+            .map(Duration::from_secs)
+            .or(default_config.max_lifetime),
+    }
+}
+
+fn session_config() -> SessionConfig {
+    let secret = match extract_var("SESSION_SECRET") {
+        Ok(secret) => secret,
+        _ => panic!("SESSION_SECRET must be set"),
+    };
+
+    SessionConfig {
+        secret,
+        lifetime: chrono::Duration::seconds(
+            extract_optional_number("SESSION_LIFETIME").unwrap_or(24 * 60 * 60),
+        ),
+    }
+}
+
+/// Charges the monthly rules of every team for each month which hasn't been
+/// run yet, up to the current one, so the months missed while the server was
+/// down are caught up at the next tick. The teams whose grace period is over
+/// are deleted at every tick.
+fn start_scheduler(pool: DbPool) {
+    let interval =
+        Duration::from_secs(extract_optional_number("SCHEDULER_INTERVAL").unwrap_or(60 * 60));
+
+    thread::spawn(move || loop {
+        let today = Local::today().naive_local();
+
+        let result = get_db_connection(&pool)
+            .map_err(PriceError::from)
+            .and_then(|db_connection| {
+                run_missing_months(&AuditedDb::new(&db_connection, Actor::scheduler()), today)
+            });
+
+        match result {
+            Ok(0) => {}
+            Ok(charged_months) => info!("Charged monthly rules of {} months", charged_months),
+            Err(err) => error!("Failed to charge monthly rules: {:?}", err),
+        }
+
+        let result = get_db_connection(&pool).and_then(|db_connection| {
+            AuditedDb::new(&db_connection, Actor::scheduler()).purge_teams()
+        });
+
+        match result {
+            Ok(ref teams) if teams.is_empty() => {}
+            Ok(teams) => info!(
+                "Deleted {} teams at the end of their grace period",
+                teams.len()
+            ),
+            Err(err) => error!("Failed to delete teams: {:?}", err),
+        }
+
+        thread::sleep(interval);
+    });
+}
+
 fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
     let port = match extract_var("PORT") {
         Ok(port) => port,
         _ => panic!("PORT must be set"),
@@ -32,9 +130,14 @@ fn main() {
         _ => panic!("DATABASE_URL must be set"),
     };
 
+    let pool = init_db_pool(&database_url, &pool_config());
+    let session_config = session_config();
+
+    start_scheduler(pool.clone());
+
     start_server(format!("0.0.0.0:{}", port), move |request| {
-        with_cors(match init_db_connection(&database_url) {
-            Ok(db_connection) => handle_request(request, &db_connection),
+        with_cors(match get_db_connection(&pool) {
+            Ok(db_connection) => handle_request(request, &db_connection, &session_config),
             Err(err) => {
                 let error_response: ErrorResponse = err.into();
                 error_response.into()
@@ -0,0 +1,18 @@
+use chrono::naive::NaiveDate;
+use uuid::Uuid;
+
+use super::models::{Balance, CreatePayment, Payment};
+use crate::database::postgres::DbError;
+
+pub trait PaymentsDb {
+    fn get_payments(&self, team_id: Uuid, user_id: Option<Uuid>) -> Result<Vec<Payment>, DbError>;
+
+    fn create_payments(&self, payments: &[CreatePayment]) -> Result<Vec<Payment>, DbError>;
+
+    fn get_balances(
+        &self,
+        team_id: Uuid,
+        user_id: Option<Uuid>,
+        date_interval: Option<(NaiveDate, NaiveDate)>,
+    ) -> Result<Vec<Balance>, DbError>;
+}
@@ -0,0 +1,117 @@
+use chrono::naive::NaiveDate;
+use diesel::{
+    sql_types::{BigInt, Uuid as UuidType},
+    Insertable, Queryable, QueryableByName,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::schema::payments;
+use crate::teams::utils::currency::Priced;
+
+#[derive(Deserialize)]
+pub struct CreatePaymentRequest {
+    pub id: Option<Uuid>,
+    pub sanction_id: Uuid,
+    pub amount: Option<i64>,
+    pub created_at: Option<NaiveDate>,
+}
+
+impl From<(CreatePaymentRequest, Uuid, Uuid, i64)> for CreatePayment {
+    fn from(
+        (create_request, team_id, user_id, amount): (CreatePaymentRequest, Uuid, Uuid, i64),
+    ) -> CreatePayment {
+        CreatePayment {
+            id: create_request.id.unwrap_or_else(Uuid::new_v4),
+            team_id,
+            user_id,
+            sanction_id: create_request.sanction_id,
+            amount,
+            created_at: create_request.created_at,
+        }
+    }
+}
+
+#[derive(Queryable, Debug, PartialEq, Serialize, Clone)]
+pub struct Payment {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub user_id: Uuid,
+    pub sanction_id: Uuid,
+    /// In cents.
+    pub amount: i64,
+    pub created_at: NaiveDate,
+}
+
+impl Priced for Payment {
+    fn amounts(&self) -> Vec<(&'static str, i64)> {
+        vec![("amount", self.amount)]
+    }
+}
+
+impl Default for Payment {
+    fn default() -> Payment {
+        Payment {
+            id: Default::default(),
+            team_id: Default::default(),
+            user_id: Default::default(),
+            sanction_id: Default::default(),
+            amount: Default::default(),
+            created_at: NaiveDate::from_ymd(2019, 10, 5),
+        }
+    }
+}
+
+#[derive(Insertable, Default)]
+#[table_name = "payments"]
+pub struct CreatePayment {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub user_id: Uuid,
+    pub sanction_id: Uuid,
+    pub amount: i64,
+    pub created_at: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    Unpaid,
+    PartiallyPaid,
+    Paid,
+}
+
+impl PaymentStatus {
+    pub fn from_amounts(price: i64, paid: i64) -> PaymentStatus {
+        if paid >= price {
+            PaymentStatus::Paid
+        } else if paid > 0 {
+            PaymentStatus::PartiallyPaid
+        } else {
+            PaymentStatus::Unpaid
+        }
+    }
+}
+
+/// Sums the sanctions of a user and the payments made against them, in cents.
+#[derive(QueryableByName, Debug, PartialEq, Serialize, Clone, Default)]
+pub struct Balance {
+    #[sql_type = "UuidType"]
+    pub user_id: Uuid,
+    #[sql_type = "BigInt"]
+    pub total_owed: i64,
+    #[sql_type = "BigInt"]
+    pub total_paid: i64,
+    #[sql_type = "BigInt"]
+    pub outstanding: i64,
+}
+
+impl Priced for Balance {
+    fn amounts(&self) -> Vec<(&'static str, i64)> {
+        vec![
+            ("total_owed", self.total_owed),
+            ("total_paid", self.total_paid),
+            ("outstanding", self.outstanding),
+        ]
+    }
+}
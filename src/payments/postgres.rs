@@ -0,0 +1,406 @@
+use chrono::naive::NaiveDate;
+use diesel::{
+    prelude::*,
+    sql_types::{Date, Nullable, Uuid as UuidType},
+};
+use std::ops::Deref;
+use uuid::Uuid;
+
+use super::{
+    interface::PaymentsDb,
+    models::{Balance, CreatePayment, Payment},
+};
+use crate::database::{
+    postgres::{DbConnection, DbError},
+    schema::{payments, sanctions},
+};
+
+/// Every user of the team gets a balance, even without any sanction. Only the
+/// approved sanctions created in the interval, if any, and their payments are
+/// summed.
+const BALANCES_QUERY: &str = "
+    SELECT users.id AS user_id,
+        COALESCE(SUM(owed.price), 0)::BIGINT AS total_owed,
+        COALESCE(SUM(owed.paid), 0)::BIGINT AS total_paid,
+        COALESCE(SUM(owed.price - owed.paid), 0)::BIGINT AS outstanding
+    FROM users
+    LEFT JOIN (
+        SELECT sanctions.user_id, sanctions.price, COALESCE(SUM(payments.amount), 0) AS paid
+        FROM sanctions
+        LEFT JOIN payments ON payments.sanction_id = sanctions.id
+        WHERE sanctions.team_id = $1 AND sanctions.deleted_at IS NULL
+            AND sanctions.approval_status = 'APPROVED'
+            AND ($3 IS NULL OR sanctions.created_at BETWEEN $3 AND $4)
+        GROUP BY sanctions.id
+    ) AS owed ON owed.user_id = users.id
+    WHERE users.team_id = $1 AND ($2 IS NULL OR users.id = $2)
+    GROUP BY users.id
+    ORDER BY users.id";
+
+impl PaymentsDb for DbConnection {
+    fn get_payments(&self, team_id: Uuid, user_id: Option<Uuid>) -> Result<Vec<Payment>, DbError> {
+        let payments = match user_id {
+            Some(user_id) => payments::table
+                .filter(
+                    payments::team_id
+                        .eq(team_id)
+                        .and(payments::user_id.eq(user_id)),
+                )
+                .get_results(self.deref())?,
+            None => payments::table
+                .filter(payments::team_id.eq(team_id))
+                .get_results(self.deref())?,
+        };
+
+        Ok(payments)
+    }
+
+    fn create_payments(&self, payments: &[CreatePayment]) -> Result<Vec<Payment>, DbError> {
+        self.deref().transaction(|| {
+            // The sanctions stay locked until the payments are created, so two
+            // concurrent payments can't both fit in the same remaining amount.
+            let sanction_ids: Vec<Uuid> =
+                payments.iter().map(|payment| payment.sanction_id).collect();
+
+            let prices: Vec<(Uuid, i64)> = sanctions::table
+                .filter(sanctions::id.eq_any(&sanction_ids))
+                .select((sanctions::id, sanctions::price))
+                .for_update()
+                .get_results(self.deref())?;
+            let paid: Vec<(Uuid, i64)> = payments::table
+                .filter(payments::sanction_id.eq_any(&sanction_ids))
+                .select((payments::sanction_id, payments::amount))
+                .get_results(self.deref())?;
+
+            for (sanction_id, price) in prices {
+                let total: i64 = paid
+                    .iter()
+                    .cloned()
+                    .chain(
+                        payments
+                            .iter()
+                            .map(|payment| (payment.sanction_id, payment.amount)),
+                    )
+                    .filter(|(paid_sanction_id, _)| *paid_sanction_id == sanction_id)
+                    .map(|(_, amount)| amount)
+                    .sum();
+
+                if total > price {
+                    return Err(DbError::UniqueViolation(format!(
+                        "The sanction {} has been paid meanwhile by another payment",
+                        sanction_id
+                    )));
+                }
+            }
+
+            let payments: Vec<Payment> = diesel::insert_into(payments::table)
+                .values(payments)
+                .get_results(self.deref())?;
+
+            Ok(payments)
+        })
+    }
+
+    fn get_balances(
+        &self,
+        team_id: Uuid,
+        user_id: Option<Uuid>,
+        date_interval: Option<(NaiveDate, NaiveDate)>,
+    ) -> Result<Vec<Balance>, DbError> {
+        let balances: Vec<Balance> = diesel::sql_query(BALANCES_QUERY)
+            .bind::<UuidType, _>(team_id)
+            .bind::<Nullable<UuidType>, _>(user_id)
+            .bind::<Nullable<Date>, _>(date_interval.map(|(min, _)| min))
+            .bind::<Nullable<Date>, _>(date_interval.map(|(_, max)| max))
+            .load(self.deref())?;
+
+        Ok(balances)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use diesel::result::Error;
+
+    use super::*;
+    use crate::sanctions::{
+        interface::SanctionsDb,
+        models::{ApprovalStatus, CreateSanction},
+    };
+    use crate::test_utils::postgres::{create_team_with_rule, init_connection};
+    use crate::users::{interface::UsersDb, models::User};
+
+    #[test]
+    fn test_get_payments() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id_2 = conn
+                .create_user(&User {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction_ids: Vec<Uuid> = conn
+                .create_sanctions(&[
+                    CreateSanction {
+                        user_id,
+                        team_id,
+                        ..Default::default()
+                    },
+                    CreateSanction {
+                        id: Uuid::new_v4(),
+                        user_id: user_id_2,
+                        team_id,
+                        ..Default::default()
+                    },
+                ])
+                .unwrap()
+                .into_iter()
+                .map(|sanction| sanction.id)
+                .collect();
+
+            let payment = conn
+                .create_payments(&[CreatePayment {
+                    team_id,
+                    user_id,
+                    sanction_id: sanction_ids[0],
+                    ..Default::default()
+                }])
+                .unwrap();
+            let payment_2 = conn
+                .create_payments(&[CreatePayment {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    user_id: user_id_2,
+                    sanction_id: sanction_ids[1],
+                    ..Default::default()
+                }])
+                .unwrap();
+
+            assert_eq!(conn.get_payments(team_id, None).unwrap().len(), 2);
+            assert_eq!(conn.get_payments(team_id, Some(user_id)).unwrap(), payment);
+            assert_eq!(
+                conn.get_payments(team_id, Some(user_id_2)).unwrap(),
+                payment_2
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_create_payments() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let id = Uuid::new_v4();
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction_id = conn
+                .create_sanctions(&[CreateSanction {
+                    user_id,
+                    team_id,
+                    price: 500,
+                    ..Default::default()
+                }])
+                .unwrap()[0]
+                .id;
+
+            let payments = conn
+                .create_payments(&[CreatePayment {
+                    id,
+                    team_id,
+                    user_id,
+                    sanction_id,
+                    amount: 250,
+                    created_at: None,
+                }])
+                .unwrap();
+
+            assert_eq!(payments[0].id, id);
+            assert_eq!(payments[0].sanction_id, sanction_id);
+            assert_eq!(payments[0].amount, 250);
+            assert_eq!(payments[0].created_at, Local::today().naive_local());
+
+            let error = conn
+                .create_payments(&[CreatePayment {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    user_id,
+                    sanction_id,
+                    amount: 300,
+                    created_at: None,
+                }])
+                .unwrap_err();
+
+            assert_eq!(
+                error,
+                DbError::UniqueViolation(format!(
+                    "The sanction {} has been paid meanwhile by another payment",
+                    sanction_id
+                ))
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_get_balances() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id_2 = conn
+                .create_user(&User {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanctions = conn
+                .create_sanctions(&[
+                    CreateSanction {
+                        user_id,
+                        team_id,
+                        price: 500,
+                        created_at: Some(NaiveDate::from_ymd(2019, 10, 5)),
+                        ..Default::default()
+                    },
+                    CreateSanction {
+                        id: Uuid::new_v4(),
+                        user_id,
+                        team_id,
+                        price: 300,
+                        created_at: Some(NaiveDate::from_ymd(2019, 11, 5)),
+                        ..Default::default()
+                    },
+                    CreateSanction {
+                        id: Uuid::new_v4(),
+                        user_id: user_id_2,
+                        team_id,
+                        price: 200,
+                        approval_status: ApprovalStatus::Pending,
+                        ..Default::default()
+                    },
+                ])
+                .unwrap();
+            conn.create_payments(&[
+                CreatePayment {
+                    team_id,
+                    user_id,
+                    sanction_id: sanctions[0].id,
+                    amount: 150,
+                    ..Default::default()
+                },
+                CreatePayment {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    user_id,
+                    sanction_id: sanctions[0].id,
+                    amount: 200,
+                    ..Default::default()
+                },
+            ])
+            .unwrap();
+
+            let balances = conn.get_balances(team_id, None, None).unwrap();
+            let balance = balances
+                .iter()
+                .find(|balance| balance.user_id == user_id)
+                .unwrap();
+            let balance_2 = balances
+                .iter()
+                .find(|balance| balance.user_id == user_id_2)
+                .unwrap();
+
+            assert_eq!(balances.len(), 2);
+            assert_eq!(balance.total_owed, 800);
+            assert_eq!(balance.total_paid, 350);
+            assert_eq!(balance.outstanding, 450);
+            assert_eq!(balance_2.total_owed, 0);
+
+            let balances = conn
+                .get_balances(
+                    team_id,
+                    Some(user_id),
+                    Some((
+                        NaiveDate::from_ymd(2019, 11, 1),
+                        NaiveDate::from_ymd(2019, 11, 30),
+                    )),
+                )
+                .unwrap();
+
+            assert_eq!(
+                balances,
+                vec![Balance {
+                    user_id,
+                    total_owed: 300,
+                    total_paid: 0,
+                    outstanding: 300,
+                }]
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_create_payments_fails() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let error = conn
+                .create_payments(&[CreatePayment {
+                    team_id,
+                    user_id,
+                    ..Default::default()
+                }])
+                .unwrap_err();
+
+            assert_eq!(
+                error,
+                DbError::ForeignKeyViolation(String::from(
+                    "The key sanction_id doesn\'t refer to anything"
+                ))
+            );
+
+            Ok(())
+        });
+    }
+}
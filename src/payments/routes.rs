@@ -0,0 +1,309 @@
+use rouille::{input::json::json_input, router, Request};
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{
+    interface::PaymentsDb,
+    models::{Balance, CreatePayment, CreatePaymentRequest, Payment},
+};
+use crate::api::models::ErrorResponse;
+use crate::database::postgres::DbError;
+use crate::sanctions::{
+    interface::SanctionsDb, models::ApprovalStatus, utils::parameters::ParametersHandler,
+};
+use crate::teams::{
+    interface::TeamsDb,
+    utils::currency::{Currency, WithCurrency},
+};
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum ResultWrapper {
+    Payments(Vec<WithCurrency<Payment>>),
+    Balances(Vec<WithCurrency<Balance>>),
+    Balance(WithCurrency<Balance>),
+}
+
+pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResultWrapper, ErrorResponse>
+where
+    T: PaymentsDb + SanctionsDb + TeamsDb,
+{
+    router!(request,
+        (GET) (/teams/{team_id: Uuid}/balances) => {
+            let parameters_handler = ParametersHandler::from_request(request)?;
+
+            let result = db.get_balances(team_id, None, parameters_handler.date_interval())?;
+
+            Ok(ResultWrapper::Balances(Currency::of_team(db, team_id)?.apply_all(result)))
+        },
+        (GET) (/teams/{team_id: Uuid}/users/{user_id: Uuid}/balance) => {
+            let parameters_handler = ParametersHandler::from_request(request)?;
+
+            let result = db
+                .get_balances(team_id, Some(user_id), parameters_handler.date_interval())?
+                .pop()
+                .ok_or(DbError::NotFound)?;
+
+            Ok(ResultWrapper::Balance(Currency::of_team(db, team_id)?.apply(result)))
+        },
+        (GET) (/teams/{team_id: Uuid}/users/{user_id: Uuid}/payments) => {
+            let result = db.get_payments(team_id, Some(user_id))?;
+
+            Ok(ResultWrapper::Payments(Currency::of_team(db, team_id)?.apply_all(result)))
+        },
+        (POST) (/teams/{team_id: Uuid}/users/{user_id: Uuid}/payments) => {
+            let input = json_input::<Vec<CreatePaymentRequest>>(request)?;
+
+            let mut paid_by_sanction: HashMap<Uuid, i64> = HashMap::new();
+            db.get_payments(team_id, Some(user_id))?
+                .into_iter()
+                .for_each(|payment| {
+                    *paid_by_sanction.entry(payment.sanction_id).or_insert(0) += payment.amount;
+                });
+
+            let mut payments: Vec<CreatePayment> = vec![];
+
+            for create_request in input {
+                let sanction = db
+                    .get_sanction(team_id, create_request.sanction_id)
+                    .map_err(|err| match err {
+                        DbError::NotFound => DbError::ForeignKeyViolation(String::from(
+                            "The key sanction_id doesn't refer to anything",
+                        )),
+                        _ => err,
+                    })?;
+
+                if sanction.user_id != user_id {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The sanction {} doesn't belong to the user {}",
+                        sanction.id, user_id
+                    )));
+                }
+
+                if sanction.approval_status != ApprovalStatus::Approved {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The sanction {} hasn't been approved and can't be paid",
+                        sanction.id
+                    )));
+                }
+
+                let paid = paid_by_sanction.entry(sanction.id).or_insert(0);
+                let remaining = sanction.price - *paid;
+                let amount = create_request.amount.unwrap_or(remaining);
+
+                if amount <= 0 || amount > remaining {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The amount of a payment for the sanction {} must be greater than 0 and can't exceed {}",
+                        sanction.id, remaining
+                    )));
+                }
+
+                *paid += amount;
+
+                payments.push((create_request, team_id, user_id, amount).into());
+            }
+
+            let result = db.create_payments(&payments)?;
+
+            Ok(ResultWrapper::Payments(Currency::of_team(db, team_id)?.apply_all(result)))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
+    use crate::sanctions::models::Sanction;
+    use crate::test_utils::routes::{DbMock, PaymentsDbMock, SanctionsDbMock};
+
+    #[test]
+    fn test_get_balances() {
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/balances?month=10&year=2019", team_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["total_owed"], json!(500));
+        assert_eq!(response[0]["outstanding"], json!(300));
+        assert_eq!(response[0]["currency"], json!("EUR"));
+        assert_eq!(response[0]["formatted"]["outstanding"], json!("3,00 €"));
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/balances?month=10", team_id)),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadParameter);
+    }
+
+    #[test]
+    fn test_get_balance() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/users/{}/balance", team_id, user_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["user_id"], json!(user_id));
+        assert_eq!(response["total_paid"], json!(200));
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/users/{}/balance", team_id, user_id)),
+            &DbMock {
+                payments_db: PaymentsDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_get_payments() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/users/{}/payments", team_id, user_id)),
+            &DbMock {
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment::default()]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["team_id"], json!(team_id));
+        assert_eq!(response[0]["user_id"], json!(user_id));
+    }
+
+    #[test]
+    fn test_create_payments() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+
+        let payments = json!([
+            { "sanction_id": sanction_id, "amount": 150 },
+            { "sanction_id": sanction_id }
+        ]);
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/users/{}/payments", team_id, user_id),
+                &payments
+            ),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    price: 500,
+                    ..Default::default()
+                })),
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                    sanction_id,
+                    amount: 100,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["amount"], json!(150));
+        assert_eq!(response[1]["amount"], json!(250));
+        assert_eq!(response[1]["sanction_id"], json!(sanction_id));
+        assert_eq!(response[1]["user_id"], json!(user_id));
+    }
+
+    #[test]
+    fn test_create_payments_fails() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let url = format!("/teams/{}/users/{}/payments", team_id, user_id);
+        let db = DbMock {
+            sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                user_id,
+                price: 500,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                url.clone(),
+                &json!([{ "sanction_id": Uuid::new_v4(), "amount": 600 }]),
+            ),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                url.clone(),
+                &json!([{ "sanction_id": Uuid::new_v4(), "amount": 0 }]),
+            ),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        let error = handle_request(
+            &RequestBuilder::post(url.clone(), &json!([{ "sanction_id": Uuid::new_v4() }])),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        for approval_status in [ApprovalStatus::Pending, ApprovalStatus::Rejected] {
+            let error = handle_request(
+                &RequestBuilder::post(url.clone(), &json!([{ "sanction_id": Uuid::new_v4() }])),
+                &DbMock {
+                    sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                        user_id,
+                        price: 500,
+                        approval_status,
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+
+            assert_eq!(error.kind, ErrorKind::NotValid);
+        }
+
+        let error = handle_request(
+            &RequestBuilder::post(url.clone(), &json!([{ "sanction_id": Uuid::new_v4() }])),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadReference);
+
+        let error = handle_request(&RequestBuilder::post(url, &json!({})), &db).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Json);
+    }
+}
@@ -1,7 +1,7 @@
 use chrono::naive::NaiveDate;
 use uuid::Uuid;
 
-use super::models::{CreateSanction, Sanction};
+use super::models::{CreateSanction, Sanction, UpdateAppeal, UpdateApproval, UpdateSanction};
 use crate::database::postgres::DbError;
 
 pub trait SanctionsDb {
@@ -9,9 +9,38 @@ pub trait SanctionsDb {
         &self,
         team_id: Uuid,
         date_interval: Option<(NaiveDate, NaiveDate)>,
+        include_deleted: bool,
     ) -> Result<Vec<Sanction>, DbError>;
 
+    fn get_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError>;
+
+    /// Each sanction refers to the current version of its rule.
     fn create_sanctions(&self, sanctions: &[CreateSanction]) -> Result<Vec<Sanction>, DbError>;
 
+    fn update_sanction(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        sanction: &UpdateSanction,
+    ) -> Result<Sanction, DbError>;
+
+    fn update_appeal(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        appeal: &UpdateAppeal,
+    ) -> Result<Sanction, DbError>;
+
+    /// An approved sanction is referred to the current version of its rule,
+    /// against which it has just been priced again.
+    fn update_approval(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        approval: &UpdateApproval,
+    ) -> Result<Sanction, DbError>;
+
     fn delete_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError>;
+
+    fn restore_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError>;
 }
@@ -1,12 +1,21 @@
-use chrono::naive::NaiveDate;
-use diesel::{Insertable, Queryable};
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Local,
+};
+use diesel::{sql_types::Varchar, AsChangeset, Insertable, Queryable};
 use diesel_as_jsonb::AsJsonb;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
-use crate::database::schema::sanctions;
-use crate::teams::models::{Rule, RuleKind};
+use super::interface::SanctionsDb;
+use crate::database::{postgres::DbError, schema::sanctions};
+use crate::payments::models::PaymentStatus;
+use crate::teams::{
+    models::{PriceLimits, ResetPeriod, Rule, RuleKind, RuleVersion},
+    utils::currency::Priced,
+};
+use crate::varchar_enum;
 
 #[derive(Deserialize)]
 pub struct UpdateSanctionRequest {
@@ -16,17 +25,20 @@ pub struct UpdateSanctionRequest {
     pub created_at: Option<NaiveDate>,
 }
 
-impl From<(UpdateSanctionRequest, Uuid, f32)> for CreateSanction {
+impl From<(UpdateSanctionRequest, Uuid, Price)> for CreateSanction {
     fn from(
-        (update_request, team_id, price): (UpdateSanctionRequest, Uuid, f32),
+        (update_request, team_id, price): (UpdateSanctionRequest, Uuid, Price),
     ) -> CreateSanction {
         CreateSanction {
             id: update_request.id.unwrap_or_else(Uuid::new_v4),
             user_id: update_request.user_id,
             team_id,
             sanction_info: update_request.sanction_info,
-            price,
+            price: price.value,
             created_at: update_request.created_at,
+            applied_limit: price.applied_limit,
+            approval_status: ApprovalStatus::Approved,
+            proposed_by: None,
         }
     }
 }
@@ -37,8 +49,30 @@ pub struct Sanction {
     pub user_id: Uuid,
     pub team_id: Uuid,
     pub sanction_info: SanctionInfo,
-    pub price: f32,
+    /// In cents.
+    pub price: i64,
     pub created_at: NaiveDate,
+    /// The first day of the month charged by a monthly rule, which the
+    /// database only lets a user be charged once.
+    #[serde(skip_serializing)]
+    pub charged_month: Option<NaiveDate>,
+    pub applied_limit: Option<PriceLimit>,
+    /// Deleted sanctions are kept until they are restored.
+    pub deleted_at: Option<NaiveDateTime>,
+    pub appeal_status: Option<AppealStatus>,
+    /// Given by the sanctioned user when contesting the sanction.
+    pub appeal_reason: Option<String>,
+    /// Given by the admin when deciding on the appeal.
+    pub appeal_comment: Option<String>,
+    pub approval_status: ApprovalStatus,
+    /// The user who proposed the sanction, if it has been proposed.
+    pub proposed_by: Option<Uuid>,
+    /// Mirrors `sanction_info.associated_rule` for the foreign key, which
+    /// keeps the rule from being deleted, so that it can only be archived.
+    #[serde(skip_serializing)]
+    pub rule_id: Option<Uuid>,
+    /// The version of the rule under which the sanction was priced.
+    pub rule_version_id: Option<Uuid>,
 }
 
 impl Default for Sanction {
@@ -50,10 +84,54 @@ impl Default for Sanction {
             sanction_info: Default::default(),
             price: Default::default(),
             created_at: NaiveDate::from_ymd(2019, 10, 5),
+            charged_month: None,
+            applied_limit: None,
+            deleted_at: None,
+            appeal_status: None,
+            appeal_reason: None,
+            appeal_comment: None,
+            approval_status: ApprovalStatus::Approved,
+            proposed_by: None,
+            rule_id: None,
+            rule_version_id: None,
         }
     }
 }
 
+impl Priced for Sanction {
+    fn amounts(&self) -> Vec<(&'static str, i64)> {
+        vec![("price", self.price)]
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+pub struct SanctionWithStatus {
+    #[serde(flatten)]
+    pub sanction: Sanction,
+    pub paid: i64,
+    pub status: PaymentStatus,
+}
+
+impl Priced for SanctionWithStatus {
+    fn amounts(&self) -> Vec<(&'static str, i64)> {
+        vec![("price", self.sanction.price), ("paid", self.paid)]
+    }
+}
+
+/// Gives the terms of the rule as they were when the sanction was issued.
+#[derive(Debug, PartialEq, Serialize, Clone)]
+pub struct SanctionWithRule {
+    #[serde(flatten)]
+    pub sanction: Sanction,
+    pub rule_version: Option<RuleVersion>,
+}
+
+impl Priced for SanctionWithRule {
+    fn amounts(&self) -> Vec<(&'static str, i64)> {
+        vec![("price", self.sanction.price)]
+    }
+}
+
 #[derive(Insertable, Default)]
 #[table_name = "sanctions"]
 pub struct CreateSanction {
@@ -61,10 +139,167 @@ pub struct CreateSanction {
     pub user_id: Uuid,
     pub team_id: Uuid,
     pub sanction_info: SanctionInfo,
-    pub price: f32,
+    pub price: i64,
     pub created_at: Option<NaiveDate>,
+    pub applied_limit: Option<PriceLimit>,
+    pub approval_status: ApprovalStatus,
+    pub proposed_by: Option<Uuid>,
+}
+
+/// Replaces every field of an existing sanction but its id and its team. A
+/// `None` limit is stored, so a price which is no longer limited is cleared.
+#[derive(AsChangeset)]
+#[table_name = "sanctions"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateSanction {
+    pub user_id: Uuid,
+    pub sanction_info: SanctionInfo,
+    pub price: i64,
+    pub created_at: NaiveDate,
+    pub applied_limit: Option<PriceLimit>,
+}
+
+#[derive(Deserialize)]
+pub struct ContestSanctionRequest {
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct DecideAppealRequest {
+    pub decision: AppealDecision,
+    /// The reduced price, in cents, required by `AppealDecision::Reduce`.
+    pub price: Option<i64>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppealDecision {
+    Cancel,
+    Reduce,
+    Reject,
+}
+
+/// A contested sanction keeps its price until an admin decides on the appeal.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum AppealStatus {
+    Contested,
+    Cancelled,
+    Reduced,
+    Rejected,
+}
+
+varchar_enum!(AppealStatus);
+
+/// Sanctions proposed by members stay pending, and aren't owed, until they are
+/// approved.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow, Default,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum ApprovalStatus {
+    Pending,
+    #[default]
+    Approved,
+    Rejected,
 }
 
+varchar_enum!(ApprovalStatus);
+
+/// The price of a pending sanction is only final once it is approved.
+#[derive(AsChangeset)]
+#[table_name = "sanctions"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateApproval {
+    pub approval_status: ApprovalStatus,
+    pub price: i64,
+    pub applied_limit: Option<PriceLimit>,
+}
+
+/// `None` fields are left unchanged, so the price is only updated when the
+/// appeal is accepted.
+#[derive(AsChangeset)]
+#[table_name = "sanctions"]
+pub struct UpdateAppeal {
+    pub appeal_status: AppealStatus,
+    pub appeal_reason: Option<String>,
+    pub appeal_comment: Option<String>,
+    pub price: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Price {
+    pub value: i64,
+    pub applied_limit: Option<PriceLimit>,
+}
+
+impl Price {
+    /// Applies the limits of the rule, given the totals of the previous
+    /// sanctions of the user within the periods of the floor and of the cap.
+    pub fn limit(self, limits: &PriceLimits, floor_total: i64, cap_total: i64) -> Price {
+        let mut price = self;
+
+        if let Some(min_price) = limits.min_price {
+            if price.value < min_price {
+                price = Price {
+                    value: min_price,
+                    applied_limit: Some(PriceLimit::MinPrice),
+                };
+            }
+        }
+
+        if let Some(max_price) = limits.max_price {
+            if price.value > max_price {
+                price = Price {
+                    value: max_price,
+                    applied_limit: Some(PriceLimit::MaxPrice),
+                };
+            }
+        }
+
+        if let Some(period_floor) = limits.period_floor {
+            let missing = period_floor.min_price - floor_total;
+
+            if price.value < missing {
+                price = Price {
+                    value: missing,
+                    applied_limit: Some(PriceLimit::PeriodFloor),
+                };
+            }
+        }
+
+        if let Some(period_cap) = limits.period_cap {
+            let remaining = (period_cap.max_price - cap_total).max(0);
+
+            if price.value > remaining {
+                price = Price {
+                    value: remaining,
+                    applied_limit: Some(PriceLimit::PeriodCap),
+                };
+            }
+        }
+
+        price
+    }
+}
+
+/// The limit of the rule which changed the price of a sanction, if any.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum PriceLimit {
+    MinPrice,
+    MaxPrice,
+    PeriodCap,
+    PeriodFloor,
+}
+
+varchar_enum!(PriceLimit);
+
+#[derive(Debug)]
 pub struct SanctionInfoError {
     pub associated_rule_name: String,
     pub associated_rule_kind: String,
@@ -78,28 +313,165 @@ pub struct SanctionInfo {
 }
 
 impl SanctionInfo {
-    pub fn get_price(&self, rule: Rule) -> Result<f32, SanctionInfoError> {
-        match self.extra_info {
+    pub fn get_price<T>(&self, rule: Rule, context: &PricingContext<T>) -> Result<Price, PriceError>
+    where
+        T: SanctionsDb,
+    {
+        let multiply = |price: i64, factor: i64| {
+            price
+                .checked_mul(factor)
+                .ok_or_else(|| PriceError::Overflow(rule.name.clone()))
+        };
+
+        let price = match self.extra_info {
             ExtraInfo::None => match rule.kind {
-                RuleKind::Basic { price } => Ok(price),
-                _ => Err(()),
+                RuleKind::Basic { price } | RuleKind::Monthly { price } => Some(price),
+                RuleKind::Escalating {
+                    ref tiers,
+                    reset_period,
+                } => {
+                    let offenses = context.previous_prices(rule.id, reset_period)?.len();
+
+                    Some(
+                        tiers
+                            .get(offenses)
+                            .or_else(|| tiers.last())
+                            .cloned()
+                            .unwrap_or_default(),
+                    )
+                }
+                _ => None,
             },
             ExtraInfo::Multiplication { factor } => match rule.kind {
                 RuleKind::Multiplication {
                     price_to_multiply, ..
-                } => Ok(price_to_multiply * (factor as f32)),
+                } => Some(multiply(price_to_multiply, i64::from(factor))?),
                 RuleKind::TimeMultiplication {
                     price_per_time_unit,
                     ..
-                } => Ok(price_per_time_unit * (factor as f32)),
-                _ => Err(()),
+                } => Some(multiply(price_per_time_unit, i64::from(factor))?),
+                _ => None,
             },
+            ExtraInfo::Duration { seconds } => match rule.kind {
+                RuleKind::TimeMultiplication {
+                    price_per_time_unit,
+                    time_unit,
+                    rounding,
+                } => Some(multiply(
+                    price_per_time_unit,
+                    time_unit.count(seconds, rounding),
+                )?),
+                _ => None,
+            },
+        };
+
+        let value = price.ok_or_else(|| {
+            PriceError::SanctionInfo(SanctionInfoError {
+                associated_rule_name: rule.name.clone(),
+                associated_rule_kind: rule.kind.to_string(),
+                extra_info: self.extra_info.to_string(),
+            })
+        })?;
+
+        let previous_total = |period: ResetPeriod| -> Result<i64, DbError> {
+            Ok(context.previous_prices(rule.id, period)?.iter().sum())
+        };
+        let floor_total = match rule.limits.period_floor {
+            Some(period_floor) => previous_total(period_floor.period)?,
+            None => 0,
+        };
+        let cap_total = match rule.limits.period_cap {
+            Some(period_cap) => previous_total(period_cap.period)?,
+            None => 0,
+        };
+
+        Ok(Price {
+            value,
+            applied_limit: None,
         }
-        .map_err(|_| SanctionInfoError {
-            associated_rule_name: rule.name,
-            associated_rule_kind: rule.kind.to_string(),
-            extra_info: self.extra_info.to_string(),
-        })
+        .limit(&rule.limits, floor_total, cap_total))
+    }
+}
+
+#[derive(Debug)]
+pub enum PriceError {
+    SanctionInfo(SanctionInfoError),
+    /// The price of the rule, named here, doesn't fit in cents.
+    Overflow(String),
+    Db(DbError),
+}
+
+impl From<DbError> for PriceError {
+    fn from(error: DbError) -> PriceError {
+        PriceError::Db(error)
+    }
+}
+
+/// Describes the sanction being priced, for the rules whose price depends on
+/// the previous sanctions of the user.
+pub struct PricingContext<'a, T> {
+    pub db: &'a T,
+    pub team_id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: NaiveDate,
+    /// Sanctions priced in the same request but not created yet.
+    pub pending: &'a [CreateSanction],
+    /// The sanction being updated, which doesn't count as a previous one.
+    pub replaced: Option<Uuid>,
+}
+
+impl<'a, T> PricingContext<'a, T>
+where
+    T: SanctionsDb,
+{
+    /// Returns the prices of the sanctions of the user for this rule since the
+    /// start of the reset period, up to the date of the sanction being priced.
+    pub fn previous_prices(
+        &self,
+        rule_id: Uuid,
+        reset_period: ResetPeriod,
+    ) -> Result<Vec<i64>, DbError> {
+        let period_start = reset_period.start(self.created_at);
+        let in_period = |date: NaiveDate| {
+            date <= self.created_at
+                && match period_start {
+                    Some(start) => date >= start,
+                    None => true,
+                }
+        };
+
+        let created = self
+            .db
+            .get_sanctions(
+                self.team_id,
+                period_start.map(|start| (start, self.created_at)),
+                false,
+            )?
+            .into_iter()
+            .filter(|sanction| {
+                sanction.user_id == self.user_id
+                    && sanction.sanction_info.associated_rule == rule_id
+                    && sanction.approval_status == ApprovalStatus::Approved
+                    && Some(sanction.id) != self.replaced
+                    && in_period(sanction.created_at)
+            })
+            .map(|sanction| sanction.price);
+
+        let pending = self
+            .pending
+            .iter()
+            .filter(|sanction| {
+                sanction.user_id == self.user_id
+                    && sanction.sanction_info.associated_rule == rule_id
+                    && in_period(
+                        sanction
+                            .created_at
+                            .unwrap_or_else(|| Local::today().naive_local()),
+                    )
+            })
+            .map(|sanction| sanction.price);
+
+        Ok(created.chain(pending).collect())
     }
 }
 
@@ -107,7 +479,13 @@ impl SanctionInfo {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type")]
 pub enum ExtraInfo {
     None,
-    Multiplication { factor: u32 },
+    Multiplication {
+        factor: u32,
+    },
+    /// A duration, such as a lateness, converted into the time unit of the rule.
+    Duration {
+        seconds: u64,
+    },
 }
 
 impl Default for ExtraInfo {
@@ -121,6 +499,7 @@ impl fmt::Display for ExtraInfo {
         match self {
             ExtraInfo::None => write!(f, "NONE"),
             ExtraInfo::Multiplication { .. } => write!(f, "MULTIPLICATION"),
+            ExtraInfo::Duration { .. } => write!(f, "DURATION"),
         }
     }
 }
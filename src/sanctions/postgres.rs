@@ -1,40 +1,86 @@
-use chrono::naive::NaiveDate;
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Utc,
+};
 use diesel::prelude::*;
 use std::ops::Deref;
 use uuid::Uuid;
 
 use super::{
     interface::SanctionsDb,
-    models::{CreateSanction, Sanction},
+    models::{
+        ApprovalStatus, CreateSanction, Sanction, UpdateAppeal, UpdateApproval, UpdateSanction,
+    },
 };
 use crate::database::{
     postgres::{DbConnection, DbError},
-    schema::sanctions,
+    schema::{rule_versions, sanctions},
 };
 
+/// The version of the rule which is currently in force, against which a
+/// sanction is priced.
+fn current_rule_version(conn: &DbConnection, rule_id: Uuid) -> Result<Option<Uuid>, DbError> {
+    let version_id: Option<Uuid> = rule_versions::table
+        .filter(rule_versions::rule_id.eq(rule_id))
+        .order(rule_versions::version.desc())
+        .select(rule_versions::id)
+        .first(conn.deref())
+        .optional()?;
+
+    Ok(version_id)
+}
+
 impl SanctionsDb for DbConnection {
     fn get_sanctions(
         &self,
         team_id: Uuid,
         date_interval: Option<(NaiveDate, NaiveDate)>,
+        include_deleted: bool,
     ) -> Result<Vec<Sanction>, DbError> {
-        let sanctions = match date_interval {
-            Some((min, max)) => sanctions::table
-                .filter(
-                    sanctions::created_at
-                        .between(min, max)
-                        .and(sanctions::team_id.eq(team_id)),
-                )
-                .get_results(self.deref())?,
-            None => sanctions::table
-                .filter(sanctions::team_id.eq(team_id))
-                .get_results(self.deref())?,
-        };
+        let mut query = sanctions::table
+            .filter(sanctions::team_id.eq(team_id))
+            .into_boxed();
+
+        if let Some((min, max)) = date_interval {
+            query = query.filter(sanctions::created_at.between(min, max));
+        }
+
+        if !include_deleted {
+            query = query.filter(sanctions::deleted_at.is_null());
+        }
+
+        let sanctions: Vec<Sanction> = query.get_results(self.deref())?;
 
         Ok(sanctions)
     }
 
+    fn get_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+        let sanction: Sanction = sanctions::table
+            .filter(
+                sanctions::team_id
+                    .eq(team_id)
+                    .and(sanctions::id.eq(sanction_id))
+                    .and(sanctions::deleted_at.is_null()),
+            )
+            .get_result(self.deref())?;
+
+        Ok(sanction)
+    }
+
     fn create_sanctions(&self, sanctions: &[CreateSanction]) -> Result<Vec<Sanction>, DbError> {
+        let sanctions = sanctions
+            .iter()
+            .map(|sanction| {
+                let rule_id = sanction.sanction_info.associated_rule;
+
+                Ok((
+                    sanction,
+                    sanctions::rule_id.eq(rule_id),
+                    sanctions::rule_version_id.eq(current_rule_version(self, rule_id)?),
+                ))
+            })
+            .collect::<Result<Vec<_>, DbError>>()?;
+
         let sanctions: Vec<Sanction> = diesel::insert_into(sanctions::table)
             .values(sanctions)
             .get_results(self.deref())?;
@@ -42,14 +88,110 @@ impl SanctionsDb for DbConnection {
         Ok(sanctions)
     }
 
+    fn update_sanction(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        sanction: &UpdateSanction,
+    ) -> Result<Sanction, DbError> {
+        let rule_id = sanction.sanction_info.associated_rule;
+
+        let sanction: Sanction = diesel::update(
+            sanctions::table.filter(
+                sanctions::team_id
+                    .eq(team_id)
+                    .and(sanctions::id.eq(sanction_id))
+                    .and(sanctions::deleted_at.is_null()),
+            ),
+        )
+        .set((
+            sanction,
+            sanctions::rule_id.eq(rule_id),
+            sanctions::rule_version_id.eq(current_rule_version(self, rule_id)?),
+        ))
+        .get_result(self.deref())?;
+
+        Ok(sanction)
+    }
+
+    fn update_appeal(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        appeal: &UpdateAppeal,
+    ) -> Result<Sanction, DbError> {
+        let sanction: Sanction = diesel::update(
+            sanctions::table.filter(
+                sanctions::team_id
+                    .eq(team_id)
+                    .and(sanctions::id.eq(sanction_id))
+                    .and(sanctions::deleted_at.is_null()),
+            ),
+        )
+        .set(appeal)
+        .get_result(self.deref())?;
+
+        Ok(sanction)
+    }
+
+    fn update_approval(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        approval: &UpdateApproval,
+    ) -> Result<Sanction, DbError> {
+        self.deref().transaction(|| {
+            let sanction: Sanction = diesel::update(
+                sanctions::table.filter(
+                    sanctions::team_id
+                        .eq(team_id)
+                        .and(sanctions::id.eq(sanction_id))
+                        .and(sanctions::deleted_at.is_null()),
+                ),
+            )
+            .set(approval)
+            .get_result(self.deref())?;
+
+            if approval.approval_status != ApprovalStatus::Approved {
+                return Ok(sanction);
+            }
+
+            let rule_version_id =
+                current_rule_version(self, sanction.sanction_info.associated_rule)?;
+
+            let sanction: Sanction = diesel::update(sanctions::table.find(sanction.id))
+                .set(sanctions::rule_version_id.eq(rule_version_id))
+                .get_result(self.deref())?;
+
+            Ok(sanction)
+        })
+    }
+
     fn delete_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
-        let sanction: Sanction = diesel::delete(
+        let sanction: Sanction = diesel::update(
             sanctions::table.filter(
                 sanctions::team_id
                     .eq(team_id)
-                    .and(sanctions::id.eq(sanction_id)),
+                    .and(sanctions::id.eq(sanction_id))
+                    .and(sanctions::deleted_at.is_null()),
             ),
         )
+        .set(sanctions::deleted_at.eq(Utc::now().naive_utc()))
+        .get_result(self.deref())?;
+
+        Ok(sanction)
+    }
+
+    fn restore_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+        let sanction: Sanction = diesel::update(
+            sanctions::table.filter(
+                sanctions::team_id
+                    .eq(team_id)
+                    .and(sanctions::id.eq(sanction_id))
+                    .and(sanctions::deleted_at.is_not_null()),
+            ),
+        )
+        .set(sanctions::deleted_at.eq(None::<NaiveDateTime>))
         .get_result(self.deref())?;
 
         Ok(sanction)
@@ -62,15 +204,19 @@ mod tests {
     use diesel::result::Error;
 
     use super::*;
-    use crate::teams::{interface::TeamsDb, models::Team};
-    use crate::test_utils::postgres::init_connection;
+    use crate::sanctions::models::{AppealStatus, ApprovalStatus, PriceLimit};
+    use crate::teams::{
+        interface::TeamsDb,
+        models::{Rule, RuleKind, Team},
+    };
+    use crate::test_utils::postgres::{create_team_with_rule, init_connection};
     use crate::users::{interface::UsersDb, models::User};
 
     #[test]
     fn test_get_sanctions() {
         let conn = init_connection();
         conn.deref().test_transaction::<_, Error, _>(|| {
-            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let team_id = create_team_with_rule(&conn);
             let user_id = conn
                 .create_user(&User {
                     team_id,
@@ -111,8 +257,8 @@ mod tests {
                 }])
                 .unwrap();
 
-            let sanctions: Vec<Sanction> = conn.get_sanctions(team_id, None).unwrap();
-            let sanctions_2: Vec<Sanction> = conn.get_sanctions(team_id_2, None).unwrap();
+            let sanctions: Vec<Sanction> = conn.get_sanctions(team_id, None, false).unwrap();
+            let sanctions_2: Vec<Sanction> = conn.get_sanctions(team_id_2, None, false).unwrap();
 
             assert_eq!(sanction, sanctions);
             assert_eq!(sanction_2, sanctions_2);
@@ -126,7 +272,7 @@ mod tests {
         let conn = init_connection();
 
         conn.deref().test_transaction::<_, Error, _>(|| {
-            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let team_id = create_team_with_rule(&conn);
             let user_id = conn
                 .create_user(&User {
                     team_id,
@@ -169,6 +315,7 @@ mod tests {
                         NaiveDate::from_ymd(2019, 10, 6),
                         NaiveDate::from_ymd(2019, 10, 20),
                     )),
+                    false,
                 )
                 .unwrap();
 
@@ -178,6 +325,43 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_get_sanction() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanctions = conn
+                .create_sanctions(&[CreateSanction {
+                    user_id,
+                    team_id,
+                    applied_limit: Some(PriceLimit::MaxPrice),
+                    ..Default::default()
+                }])
+                .unwrap();
+
+            let sanction = conn.get_sanction(team_id, sanctions[0].id).unwrap();
+
+            assert_eq!(sanction, sanctions[0]);
+            assert_eq!(sanction.applied_limit, Some(PriceLimit::MaxPrice));
+
+            let error = conn
+                .get_sanction(Uuid::new_v4(), sanctions[0].id)
+                .unwrap_err();
+
+            assert_eq!(error, DbError::NotFound);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_create_sanctions() {
         let conn = init_connection();
@@ -185,7 +369,7 @@ mod tests {
         conn.deref().test_transaction::<_, Error, _>(|| {
             let id = Uuid::new_v4();
 
-            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let team_id = create_team_with_rule(&conn);
 
             let user_id = conn
                 .create_user(&User {
@@ -244,7 +428,7 @@ mod tests {
         });
 
         conn.deref().test_transaction::<_, Error, _>(|| {
-            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let team_id = create_team_with_rule(&conn);
 
             let error = conn
                 .create_sanctions(&[CreateSanction {
@@ -262,6 +446,358 @@ mod tests {
 
             Ok(())
         });
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let error = conn
+                .create_sanctions(&[CreateSanction {
+                    user_id,
+                    team_id,
+                    ..Default::default()
+                }])
+                .unwrap_err();
+
+            assert_eq!(
+                error,
+                DbError::ForeignKeyViolation(String::from(
+                    "The key rule_id doesn\'t refer to anything"
+                ))
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_charge_monthly_rule_once() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                kind: RuleKind::Monthly { price: 1000 },
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let charge = |created_at: NaiveDate| CreateSanction {
+                id: Uuid::new_v4(),
+                user_id,
+                team_id,
+                created_at: Some(created_at),
+                ..Default::default()
+            };
+
+            let sanction = conn
+                .create_sanctions(&[charge(NaiveDate::from_ymd(2019, 10, 5))])
+                .unwrap()
+                .remove(0);
+
+            assert_eq!(
+                sanction.charged_month,
+                Some(NaiveDate::from_ymd(2019, 10, 1))
+            );
+            assert!(conn
+                .create_sanctions(&[charge(NaiveDate::from_ymd(2019, 11, 5))])
+                .is_ok());
+
+            conn.delete_sanction(team_id, sanction.id).unwrap();
+
+            assert!(conn
+                .create_sanctions(&[charge(NaiveDate::from_ymd(2019, 10, 20))])
+                .is_ok());
+            assert_eq!(
+                conn.create_sanctions(&[charge(NaiveDate::from_ymd(2019, 10, 25))])
+                    .unwrap_err(),
+                DbError::UniqueViolation(String::from(
+                    "The field monthly_charge is already used by another user"
+                ))
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_sanctions_keep_rule_version() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                name: String::from("Late"),
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction = conn
+                .create_sanctions(&[CreateSanction {
+                    team_id,
+                    user_id,
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            conn.update_rule(
+                team_id,
+                rule.id,
+                &Rule {
+                    name: String::from("Absent"),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let sanction_2 = conn
+                .create_sanctions(&[CreateSanction {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    user_id,
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            let version = conn
+                .get_rule_version(team_id, sanction.rule_version_id.unwrap())
+                .unwrap();
+            let version_2 = conn
+                .get_rule_version(team_id, sanction_2.rule_version_id.unwrap())
+                .unwrap();
+
+            assert_eq!((version.version, &version.name[..]), (1, "Late"));
+            assert_eq!((version_2.version, &version_2.name[..]), (2, "Absent"));
+            assert_eq!(
+                conn.get_sanction(team_id, sanction.id)
+                    .unwrap()
+                    .rule_version_id,
+                Some(version.id)
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_update_sanction() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id_2 = conn
+                .create_user(&User {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction = conn
+                .create_sanctions(&[CreateSanction {
+                    team_id,
+                    user_id,
+                    price: 500,
+                    applied_limit: Some(PriceLimit::MaxPrice),
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            let update_sanction = UpdateSanction {
+                user_id: user_id_2,
+                sanction_info: sanction.sanction_info.clone(),
+                price: 300,
+                created_at: sanction.created_at,
+                applied_limit: None,
+            };
+
+            let updated_sanction = conn
+                .update_sanction(team_id, sanction.id, &update_sanction)
+                .unwrap();
+
+            assert_eq!(
+                updated_sanction,
+                Sanction {
+                    user_id: user_id_2,
+                    price: 300,
+                    applied_limit: None,
+                    ..sanction.clone()
+                }
+            );
+            assert_eq!(
+                conn.get_sanction(team_id, sanction.id).unwrap(),
+                updated_sanction
+            );
+
+            let error = conn
+                .update_sanction(Uuid::new_v4(), sanction.id, &update_sanction)
+                .unwrap_err();
+
+            assert_eq!(error, DbError::NotFound);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_update_appeal() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction = conn
+                .create_sanctions(&[CreateSanction {
+                    team_id,
+                    user_id,
+                    price: 500,
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            let contested_sanction = conn
+                .update_appeal(
+                    team_id,
+                    sanction.id,
+                    &UpdateAppeal {
+                        appeal_status: AppealStatus::Contested,
+                        appeal_reason: Some(String::from("I was on time")),
+                        appeal_comment: None,
+                        price: None,
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                contested_sanction.appeal_status,
+                Some(AppealStatus::Contested)
+            );
+            assert_eq!(contested_sanction.price, 500);
+
+            let reduced_sanction = conn
+                .update_appeal(
+                    team_id,
+                    sanction.id,
+                    &UpdateAppeal {
+                        appeal_status: AppealStatus::Reduced,
+                        appeal_reason: None,
+                        appeal_comment: Some(String::from("Only 5 minutes late")),
+                        price: Some(200),
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                reduced_sanction,
+                Sanction {
+                    price: 200,
+                    appeal_status: Some(AppealStatus::Reduced),
+                    appeal_comment: Some(String::from("Only 5 minutes late")),
+                    ..contested_sanction
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_update_approval() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction = conn
+                .create_sanctions(&[CreateSanction {
+                    team_id,
+                    user_id,
+                    price: 500,
+                    approval_status: ApprovalStatus::Pending,
+                    proposed_by: Some(user_id),
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            assert_eq!(sanction.approval_status, ApprovalStatus::Pending);
+            assert_eq!(sanction.proposed_by, Some(user_id));
+
+            let approved_sanction = conn
+                .update_approval(
+                    team_id,
+                    sanction.id,
+                    &UpdateApproval {
+                        approval_status: ApprovalStatus::Approved,
+                        price: 400,
+                        applied_limit: Some(PriceLimit::MaxPrice),
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                approved_sanction,
+                Sanction {
+                    price: 400,
+                    applied_limit: Some(PriceLimit::MaxPrice),
+                    approval_status: ApprovalStatus::Approved,
+                    ..sanction
+                }
+            );
+
+            Ok(())
+        });
     }
 
     #[test]
@@ -269,7 +805,7 @@ mod tests {
         let conn = init_connection();
 
         conn.deref().test_transaction::<_, Error, _>(|| {
-            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let team_id = create_team_with_rule(&conn);
 
             let user_id = conn
                 .create_user(&User {
@@ -289,10 +825,55 @@ mod tests {
 
             let sanction_deleted = conn.delete_sanction(team_id, sanctions[0].id).unwrap();
             assert_eq!(sanctions[0].id, sanction_deleted.id);
+            assert!(sanction_deleted.deleted_at.is_some());
 
-            let sanctions = conn.get_sanctions(team_id, None).unwrap();
+            let sanctions = conn.get_sanctions(team_id, None, false).unwrap();
             assert_eq!(sanctions.len(), 0);
 
+            let sanctions = conn.get_sanctions(team_id, None, true).unwrap();
+            assert_eq!(sanctions, vec![sanction_deleted.clone()]);
+
+            let error = conn.get_sanction(team_id, sanction_deleted.id).unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_restore_sanction() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = create_team_with_rule(&conn);
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let sanction = conn
+                .create_sanctions(&[CreateSanction {
+                    team_id,
+                    user_id,
+                    ..Default::default()
+                }])
+                .unwrap()
+                .remove(0);
+
+            let error = conn.restore_sanction(team_id, sanction.id).unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            conn.delete_sanction(team_id, sanction.id).unwrap();
+
+            let sanction_restored = conn.restore_sanction(team_id, sanction.id).unwrap();
+            assert_eq!(sanction_restored, sanction);
+            assert_eq!(
+                conn.get_sanctions(team_id, None, false).unwrap(),
+                vec![sanction]
+            );
+
             Ok(())
         });
     }
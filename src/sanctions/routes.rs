@@ -1,3 +1,4 @@
+use chrono::{naive::NaiveDate, Local};
 use rouille::{input::json::json_input, router, Request};
 use serde::Serialize;
 use std::collections::HashMap;
@@ -5,83 +6,468 @@ use uuid::Uuid;
 
 use super::{
     interface::SanctionsDb,
-    models::{CreateSanction, Sanction, UpdateSanctionRequest},
-    utils::{formatter::map_by_users, parameters::ParametersHandler},
+    models::{
+        AppealDecision, AppealStatus, ApprovalStatus, ContestSanctionRequest, CreateSanction,
+        DecideAppealRequest, PricingContext, Sanction, SanctionInfo, SanctionWithRule,
+        SanctionWithStatus, UpdateAppeal, UpdateApproval, UpdateSanction, UpdateSanctionRequest,
+    },
+    utils::{
+        formatter::{map_by_users, with_status},
+        monthly::{is_charged, month_interval},
+        parameters::ParametersHandler,
+    },
 };
 use crate::api::models::ErrorResponse;
+use crate::audit::interface::AuditDb;
+use crate::audit::models::{Actor, ActorKind};
 use crate::database::postgres::DbError;
-use crate::teams::interface::TeamsDb;
+use crate::payments::interface::PaymentsDb;
+use crate::scheduler::{interface::MonthlyRunsDb, utils::runner::run_month};
+use crate::teams::{
+    interface::TeamsDb,
+    models::{Rule, RuleKind, Team},
+    utils::currency::{Currency, WithCurrency},
+};
+use crate::users::interface::UsersDb;
 
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum ResultWrapper {
-    Sanctions(Vec<Sanction>),
-    MappedSanctions(HashMap<Uuid, Vec<Sanction>>),
-    Sanction(Sanction),
+    Sanctions(Vec<WithCurrency<Sanction>>),
+    SanctionsWithStatus(Vec<WithCurrency<SanctionWithStatus>>),
+    MappedSanctions(HashMap<Uuid, Vec<WithCurrency<SanctionWithStatus>>>),
+    Sanction(Box<WithCurrency<Sanction>>),
+    SanctionWithRule(Box<WithCurrency<SanctionWithRule>>),
 }
 
 pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResultWrapper, ErrorResponse>
 where
-    T: SanctionsDb + TeamsDb,
+    T: SanctionsDb + TeamsDb + UsersDb + PaymentsDb + MonthlyRunsDb + AuditDb,
 {
     router!(request,
         (GET) (/teams/{team_id: Uuid}/sanctions) => {
             let parameters_handler = ParametersHandler::from_request(request)?;
 
-            let result = db.get_sanctions(team_id, parameters_handler.date_interval())?;
+            let sanctions = db.get_sanctions(
+                team_id,
+                parameters_handler.date_interval(),
+                parameters_handler.must_include_deleted(),
+            )?;
+            let payments = db.get_payments(team_id, None)?;
+
+            let result = with_status(sanctions, &payments);
+            let currency = Currency::of_team(db, team_id)?;
 
             if parameters_handler.must_be_formatted() {
-                Ok(ResultWrapper::MappedSanctions(map_by_users(result)))
+                Ok(ResultWrapper::MappedSanctions(
+                    map_by_users(result)
+                        .into_iter()
+                        .map(|(user_id, sanctions)| (user_id, currency.apply_all(sanctions)))
+                        .collect(),
+                ))
             } else {
-                Ok(ResultWrapper::Sanctions(result))
+                Ok(ResultWrapper::SanctionsWithStatus(currency.apply_all(result)))
             }
         },
         (POST) (/teams/{team_id: Uuid}/sanctions) => {
             let input = json_input::<Vec<UpdateSanctionRequest>>(request)?;
 
-            let mut error : Option<ErrorResponse> = None;
             let mut sanctions: Vec<CreateSanction> = vec![];
+            let mut monthly_charges: Vec<(Uuid, Uuid, NaiveDate)> = vec![];
 
-            input.into_iter().map(|update_sanction| {
-                let rule = db
-                    .get_team(team_id)
-                    .map_err(|err| match err {
-                        DbError::NotFound => {
-                            DbError::ForeignKeyViolation(String::from("The key team_id doesn't refer to anything"))
-                        }
-                        _ => err,
-                    })?
-                    .get_rule(update_sanction.sanction_info.associated_rule)
-                    .ok_or_else(|| DbError::ForeignKeyViolation(String::from(
-                            "The key associated_rule doesn't refer to anything",
-                    )))?;
+            let team = sanctioned_team(db, team_id)?;
 
-                let price = update_sanction.sanction_info.get_price(rule)?;
+            for update_sanction in input {
+                check_active_user(db, team_id, update_sanction.user_id)?;
 
-                let sanction: CreateSanction = (update_sanction, team_id, price).into();
+                let rule = associated_rule(&team, &update_sanction.sanction_info)?;
+                check_active_rule(&rule)?;
 
-                Ok(sanction)
-            })
-            .for_each(|sanction_or_error| match sanction_or_error {
-                Ok(sanction)=>sanctions.push(sanction),
-                Err(err)=> match error {
-                    Some(_)=>{},
-                    None=>error=Some(err)
-                }
-            });
+                let created_at = update_sanction
+                    .created_at
+                    .unwrap_or_else(|| Local::today().naive_local());
+
+                if let RuleKind::Monthly { .. } = rule.kind {
+                    let (first_day, last_day) = month_interval(created_at);
+                    let charge = (update_sanction.user_id, rule.id, first_day);
 
-            match error {
-                Some(err)=>Err(err),
-                None=> {
-                    let result = db.create_sanctions(&sanctions)?;
-                    Ok(ResultWrapper::Sanctions(result))
+                    if monthly_charges.contains(&charge)
+                        || is_charged(
+                            &db.get_sanctions(team_id, Some((first_day, last_day)), false)?,
+                            update_sanction.user_id,
+                            rule.id,
+                        )
+                    {
+                        return Err(already_charged(update_sanction.user_id, &rule.name, first_day).into());
+                    }
+
+                    monthly_charges.push(charge);
                 }
+
+                let price = update_sanction.sanction_info.get_price(
+                    rule,
+                    &PricingContext {
+                        db,
+                        team_id,
+                        user_id: update_sanction.user_id,
+                        created_at,
+                        pending: &sanctions,
+                        replaced: None,
+                    },
+                )?;
+
+                sanctions.push((update_sanction, team_id, price).into());
             }
+
+            let result = db.create_sanctions(&sanctions)?;
+
+            Ok(ResultWrapper::Sanctions(Currency::from(&team).apply_all(result)))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/monthly) => {
+            let parameters_handler = ParametersHandler::from_request(request)?;
+
+            let date = match parameters_handler.date_interval() {
+                Some((first_day, _)) => first_day,
+                None => Local::today().naive_local(),
+            };
+
+            let (_, result) = run_month(db, team_id, date)?;
+
+            Ok(ResultWrapper::Sanctions(Currency::of_team(db, team_id)?.apply_all(result)))
+        },
+        (GET) (/teams/{team_id: Uuid}/sanctions/proposals) => {
+            let result = db
+                .get_sanctions(team_id, None, false)?
+                .into_iter()
+                .filter(|sanction| sanction.approval_status == ApprovalStatus::Pending)
+                .collect();
+
+            Ok(ResultWrapper::Sanctions(Currency::of_team(db, team_id)?.apply_all(result)))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}/approve) => {
+            let sanction = pending_sanction(db, team_id, sanction_id)?;
+            check_active_user(db, team_id, sanction.user_id)?;
+
+            let team = db.get_team(team_id)?;
+            let rule = associated_rule(&team, &sanction.sanction_info)?;
+            check_active_rule(&rule)?;
+
+            let price = sanction.sanction_info.get_price(
+                rule,
+                &PricingContext {
+                    db,
+                    team_id,
+                    user_id: sanction.user_id,
+                    created_at: sanction.created_at,
+                    pending: &[],
+                    replaced: None,
+                },
+            )?;
+
+            let result = db.update_approval(
+                team_id,
+                sanction_id,
+                &UpdateApproval {
+                    approval_status: ApprovalStatus::Approved,
+                    price: price.value,
+                    applied_limit: price.applied_limit,
+                },
+            )?;
+
+            Ok(ResultWrapper::Sanction(Box::new(Currency::from(&team).apply(result))))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}/reject) => {
+            let sanction = pending_sanction(db, team_id, sanction_id)?;
+
+            let result = db.update_approval(
+                team_id,
+                sanction_id,
+                &UpdateApproval {
+                    approval_status: ApprovalStatus::Rejected,
+                    price: sanction.price,
+                    applied_limit: sanction.applied_limit,
+                },
+            )?;
+
+            Ok(ResultWrapper::Sanction(Box::new(Currency::of_team(db, team_id)?.apply(result))))
+        },
+        (GET) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}) => {
+            let sanction = db.get_sanction(team_id, sanction_id)?;
+            let rule_version = match sanction.rule_version_id {
+                Some(version_id) => Some(db.get_rule_version(team_id, version_id)?),
+                None => None,
+            };
+
+            let result = SanctionWithRule { sanction, rule_version };
+
+            Ok(ResultWrapper::SanctionWithRule(Box::new(
+                Currency::of_team(db, team_id)?.apply(result),
+            )))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}) => {
+            update_sanction(request, db, team_id, sanction_id)
+        },
+        (PATCH) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}) => {
+            update_sanction(request, db, team_id, sanction_id)
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}/restore) => {
+            let result = db.restore_sanction(team_id, sanction_id)?;
+
+            Ok(ResultWrapper::Sanction(Box::new(Currency::of_team(db, team_id)?.apply(result))))
         },
         (DELETE) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}) => {
             let result = db.delete_sanction(team_id, sanction_id)?;
 
-            Ok(ResultWrapper::Sanction(result))
+            Ok(ResultWrapper::Sanction(Box::new(Currency::of_team(db, team_id)?.apply(result))))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+/// Keeps the id and, unless a new one is given, the date of the sanction, while
+/// its rule is resolved again and its price recomputed.
+fn update_sanction<T>(
+    request: &Request,
+    db: &T,
+    team_id: Uuid,
+    sanction_id: Uuid,
+) -> Result<ResultWrapper, ErrorResponse>
+where
+    T: SanctionsDb + TeamsDb + UsersDb + PaymentsDb,
+{
+    let input = json_input::<UpdateSanctionRequest>(request)?;
+
+    let sanction = db.get_sanction(team_id, sanction_id)?;
+    let team = db.get_team(team_id)?;
+
+    if let Some(id) = input.id {
+        if id != sanction_id {
+            return Err(ErrorResponse::not_valid(format!(
+                "The id of the sanction {} can't be changed",
+                sanction_id
+            )));
+        }
+    }
+
+    if input.user_id != sanction.user_id {
+        check_active_user(db, team_id, input.user_id)?;
+    }
+
+    let rule = associated_rule(&team, &input.sanction_info)?;
+
+    if rule.id != sanction.sanction_info.associated_rule {
+        check_active_rule(&rule)?;
+    }
+
+    let created_at = input.created_at.unwrap_or(sanction.created_at);
+
+    if let RuleKind::Monthly { .. } = rule.kind {
+        let (first_day, last_day) = month_interval(created_at);
+        let others: Vec<Sanction> = db
+            .get_sanctions(team_id, Some((first_day, last_day)), false)?
+            .into_iter()
+            .filter(|other| other.id != sanction_id)
+            .collect();
+
+        if is_charged(&others, input.user_id, rule.id) {
+            return Err(already_charged(input.user_id, &rule.name, first_day).into());
+        }
+    }
+
+    let paid = paid_amount(db, &sanction)?;
+
+    if paid > 0 && input.user_id != sanction.user_id {
+        return Err(ErrorResponse::not_valid(format!(
+            "The sanction {} has already been paid in part and can't be given to another user",
+            sanction_id
+        )));
+    }
+
+    let price = input.sanction_info.get_price(
+        rule,
+        &PricingContext {
+            db,
+            team_id,
+            user_id: input.user_id,
+            created_at,
+            pending: &[],
+            replaced: Some(sanction_id),
+        },
+    )?;
+
+    if price.value < paid {
+        return Err(ErrorResponse::not_valid(format!(
+            "The price of the sanction {} can't be lower than the {} already paid",
+            sanction_id, paid
+        )));
+    }
+
+    let result = db.update_sanction(
+        team_id,
+        sanction_id,
+        &UpdateSanction {
+            user_id: input.user_id,
+            sanction_info: input.sanction_info,
+            price: price.value,
+            created_at,
+            applied_limit: price.applied_limit,
+        },
+    )?;
+
+    Ok(ResultWrapper::Sanction(Box::new(
+        Currency::from(&team).apply(result),
+    )))
+}
+
+/// Handles the routes which depend on the actor of the request: only the
+/// sanctioned user can contest a sanction, and proposals keep who made them.
+pub fn handle_actor_request<T>(
+    request: &Request,
+    db: &T,
+    actor: Actor,
+) -> Result<ResultWrapper, ErrorResponse>
+where
+    T: SanctionsDb + TeamsDb + UsersDb + PaymentsDb,
+{
+    router!(request,
+        (POST) (/teams/{team_id: Uuid}/sanctions/proposals) => {
+            let input = json_input::<Vec<UpdateSanctionRequest>>(request)?;
+
+            let mut sanctions: Vec<CreateSanction> = vec![];
+
+            let team = sanctioned_team(db, team_id)?;
+
+            for proposal in input {
+                check_active_user(db, team_id, proposal.user_id)?;
+
+                let rule = associated_rule(&team, &proposal.sanction_info)?;
+                check_active_rule(&rule)?;
+
+                if let RuleKind::Monthly { .. } = rule.kind {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The rule {} is charged every month and can't be proposed",
+                        rule.name
+                    )));
+                }
+
+                let created_at = proposal
+                    .created_at
+                    .unwrap_or_else(|| Local::today().naive_local());
+
+                // Only an estimate, the price is computed again on approval.
+                let price = proposal.sanction_info.get_price(
+                    rule,
+                    &PricingContext {
+                        db,
+                        team_id,
+                        user_id: proposal.user_id,
+                        created_at,
+                        pending: &[],
+                        replaced: None,
+                    },
+                )?;
+
+                sanctions.push(CreateSanction {
+                    approval_status: ApprovalStatus::Pending,
+                    proposed_by: actor.id,
+                    ..(proposal, team_id, price).into()
+                });
+            }
+
+            let result = db.create_sanctions(&sanctions)?;
+
+            Ok(ResultWrapper::Sanctions(Currency::from(&team).apply_all(result)))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}/contest) => {
+            let input = json_input::<ContestSanctionRequest>(request)?;
+
+            let sanction = db.get_sanction(team_id, sanction_id)?;
+
+            if actor.kind != ActorKind::User || actor.id != Some(sanction.user_id) {
+                return Err(ErrorResponse::forbidden(String::from(
+                    "Only the sanctioned user can contest this sanction",
+                )));
+            }
+
+            if sanction.appeal_status.is_some() {
+                return Err(ErrorResponse::not_valid(format!(
+                    "The sanction {} has already been contested",
+                    sanction_id
+                )));
+            }
+
+            if input.reason.trim().is_empty() {
+                return Err(ErrorResponse::not_valid(String::from(
+                    "The reason of an appeal can't be empty",
+                )));
+            }
+
+            let result = db.update_appeal(
+                team_id,
+                sanction_id,
+                &UpdateAppeal {
+                    appeal_status: AppealStatus::Contested,
+                    appeal_reason: Some(input.reason),
+                    appeal_comment: None,
+                    price: None,
+                },
+            )?;
+
+            Ok(ResultWrapper::Sanction(Box::new(Currency::of_team(db, team_id)?.apply(result))))
+        },
+        (POST) (/teams/{team_id: Uuid}/sanctions/{sanction_id: Uuid}/appeal) => {
+            let input = json_input::<DecideAppealRequest>(request)?;
+
+            let sanction = db.get_sanction(team_id, sanction_id)?;
+
+            if sanction.appeal_status != Some(AppealStatus::Contested) {
+                return Err(ErrorResponse::not_valid(format!(
+                    "The sanction {} isn't contested",
+                    sanction_id
+                )));
+            }
+
+            let (appeal_status, price) = match input.decision {
+                AppealDecision::Reject => (AppealStatus::Rejected, None),
+                AppealDecision::Cancel => (AppealStatus::Cancelled, Some(0)),
+                AppealDecision::Reduce => match input.price {
+                    Some(price) if price >= 0 && price < sanction.price => {
+                        (AppealStatus::Reduced, Some(price))
+                    }
+                    _ => {
+                        return Err(ErrorResponse::not_valid(format!(
+                            "A reduced price must be given, positive and lower than {}",
+                            sanction.price
+                        )))
+                    }
+                },
+            };
+
+            if let Some(price) = price {
+                let paid = paid_amount(db, &sanction)?;
+
+                if price < paid {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The price of the sanction {} can't be lower than the {} already paid",
+                        sanction_id, paid
+                    )));
+                }
+            }
+
+            let result = db.update_appeal(
+                team_id,
+                sanction_id,
+                &UpdateAppeal {
+                    appeal_status,
+                    appeal_reason: None,
+                    appeal_comment: input.comment,
+                    price,
+                },
+            )?;
+
+            Ok(ResultWrapper::Sanction(Box::new(Currency::of_team(db, team_id)?.apply(result))))
         },
         _ => {
             Err(ErrorResponse::not_found())
@@ -89,15 +475,108 @@ where
     )
 }
 
+fn sanctioned_team<T>(db: &T, team_id: Uuid) -> Result<Team, DbError>
+where
+    T: TeamsDb,
+{
+    db.get_team(team_id).map_err(|err| match err {
+        DbError::NotFound => {
+            DbError::ForeignKeyViolation(String::from("The key team_id doesn't refer to anything"))
+        }
+        _ => err,
+    })
+}
+
+/// Deactivated users keep their sanctions but can't receive new ones.
+fn check_active_user<T>(db: &T, team_id: Uuid, user_id: Uuid) -> Result<(), ErrorResponse>
+where
+    T: UsersDb,
+{
+    match db.get_user(team_id, user_id) {
+        Ok(user) if user.is_active() => Ok(()),
+        Ok(_) => Err(ErrorResponse::not_valid(format!(
+            "The user {} is deactivated and can't receive new sanctions",
+            user_id
+        ))),
+        Err(DbError::NotFound) => Err(DbError::ForeignKeyViolation(String::from(
+            "The key user_id doesn't refer to anything",
+        ))
+        .into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn associated_rule(team: &Team, sanction_info: &SanctionInfo) -> Result<Rule, DbError> {
+    team.get_rule(sanction_info.associated_rule).ok_or_else(|| {
+        DbError::ForeignKeyViolation(String::from(
+            "The key associated_rule doesn't refer to anything",
+        ))
+    })
+}
+
+/// Archived rules only remain for the sanctions which already refer to them.
+fn check_active_rule(rule: &Rule) -> Result<(), ErrorResponse> {
+    if rule.is_archived() {
+        return Err(ErrorResponse::not_valid(format!(
+            "The rule {} is archived and can't be used for new sanctions",
+            rule.name
+        )));
+    }
+
+    Ok(())
+}
+
+fn pending_sanction<T>(db: &T, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, ErrorResponse>
+where
+    T: SanctionsDb,
+{
+    let sanction = db.get_sanction(team_id, sanction_id)?;
+
+    if sanction.approval_status != ApprovalStatus::Pending {
+        return Err(ErrorResponse::not_valid(format!(
+            "The sanction {} isn't pending",
+            sanction_id
+        )));
+    }
+
+    Ok(sanction)
+}
+
+fn paid_amount<T>(db: &T, sanction: &Sanction) -> Result<i64, DbError>
+where
+    T: PaymentsDb,
+{
+    Ok(db
+        .get_payments(sanction.team_id, Some(sanction.user_id))?
+        .iter()
+        .filter(|payment| payment.sanction_id == sanction.id)
+        .map(|payment| payment.amount)
+        .sum())
+}
+
+fn already_charged(user_id: Uuid, rule_name: &str, month: NaiveDate) -> DbError {
+    DbError::UniqueViolation(format!(
+        "The user {} has already been charged the rule {} for {}",
+        user_id,
+        rule_name,
+        month.format("%Y-%m")
+    ))
+}
+
 #[cfg(test)]
 mod tests {
-    use chrono::{naive::NaiveDate, Local};
     use serde_json::json;
 
     use super::*;
     use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
-    use crate::teams::models::{Rule, RuleKind};
-    use crate::test_utils::routes::{DbMock, SanctionsDbMock, TeamsDbMock};
+    use crate::payments::models::Payment;
+    use crate::sanctions::models::{ExtraInfo, SanctionInfo};
+    use crate::teams::models::{
+        PeriodCap, PeriodFloor, PriceLimits, ResetPeriod, Rounding, Rule, TimeUnit,
+    };
+    use crate::test_utils::routes::{
+        DbMock, PaymentsDbMock, SanctionsDbMock, TeamsDbMock, UsersDbMock,
+    };
 
     #[test]
     fn test_get_sanctions() {
@@ -111,6 +590,30 @@ mod tests {
 
         for i in 0..3 {
             assert_eq!(response[i]["team_id"], json!(team_id));
+            assert_eq!(response[i]["status"], json!("PAID"));
+            assert_eq!(response[i]["currency"], json!("EUR"));
+            assert_eq!(response[i]["formatted"]["price"], json!("0,00 €"));
+        }
+    }
+
+    #[test]
+    fn test_get_sanctions_with_payments() {
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/sanctions", team_id)),
+            &DbMock {
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                    amount: 200,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        for i in 0..3 {
+            assert_eq!(response[i]["paid"], json!(200));
         }
     }
 
@@ -188,7 +691,7 @@ mod tests {
 
         let rule_1 = Rule {
             kind: RuleKind::Multiplication {
-                price_to_multiply: 3.5,
+                price_to_multiply: 350,
             },
             ..Default::default()
         };
@@ -232,7 +735,7 @@ mod tests {
 
         assert_eq!(response[0]["team_id"], json!(team_id));
         assert_eq!(response[0]["user_id"], sanctions[0]["user_id"]);
-        assert_eq!(response[0]["price"], json!(7.0));
+        assert_eq!(response[0]["price"], json!(700));
         assert_eq!(response[0]["created_at"], json!(Local::today().naive_utc()));
         assert_eq!(response[1]["created_at"], json!(created_at));
     }
@@ -243,7 +746,7 @@ mod tests {
 
         let rule = Rule {
             kind: RuleKind::Multiplication {
-                price_to_multiply: 3.5,
+                price_to_multiply: 350,
             },
             ..Default::default()
         };
@@ -289,18 +792,968 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_sanction() {
+    fn test_create_sanction_with_archived_rule_fails() {
         let team_id = Uuid::new_v4();
-        let sanction_id = Uuid::new_v4();
+        let rule = Rule {
+            name: String::from("Retard"),
+            archived_at: Some(NaiveDate::from_ymd(2019, 10, 1).and_hms(0, 0, 0)),
+            ..Default::default()
+        };
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([{
+                    "user_id": Uuid::new_v4(),
+                    "sanction_info": {
+                        "associated_rule": rule.id,
+                        "extra_info": {
+                            "type": "NONE"
+                        }
+                    }
+                }]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            "The rule Retard is archived and can't be used for new sanctions"
+        );
+    }
+
+    #[test]
+    fn test_create_sanction_with_overflowing_price_fails() {
+        let team_id = Uuid::new_v4();
+        let rule = Rule {
+            name: String::from("Retard"),
+            kind: RuleKind::Multiplication {
+                price_to_multiply: i64::MAX / 2,
+            },
+            ..Default::default()
+        };
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([{
+                    "user_id": Uuid::new_v4(),
+                    "sanction_info": {
+                        "associated_rule": rule.id,
+                        "extra_info": {
+                            "type": "MULTIPLICATION",
+                            "factor": 3
+                        }
+                    }
+                }]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            "The price of the rule Retard is too high to be computed"
+        );
+    }
+
+    #[test]
+    fn test_create_sanction_for_deactivated_user_fails() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([{
+                    "user_id": user_id,
+                    "sanction_info": {
+                        "associated_rule": Rule::default().id,
+                        "extra_info": {
+                            "type": "NONE"
+                        }
+                    }
+                }]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![Rule::default()]),
+                users_db: UsersDbMock::Deactivated,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            format!(
+                "The user {} is deactivated and can't receive new sanctions",
+                user_id
+            )
+        );
+    }
+
+    #[test]
+    fn test_create_sanction_with_duration() {
+        let team_id = Uuid::new_v4();
+
+        let rules: Vec<Rule> = [Rounding::Ceil, Rounding::Floor, Rounding::Nearest]
+            .iter()
+            .map(|rounding| Rule {
+                id: Uuid::new_v4(),
+                kind: RuleKind::TimeMultiplication {
+                    price_per_time_unit: 50,
+                    time_unit: TimeUnit::Minute,
+                    rounding: *rounding,
+                },
+                ..Default::default()
+            })
+            .collect();
+
+        let sanctions: Vec<serde_json::Value> = rules
+            .iter()
+            .map(|rule| {
+                json!({
+                    "user_id": Uuid::new_v4(),
+                    "sanction_info": {
+                        "associated_rule": rule.id,
+                        "extra_info": {
+                            "type": "DURATION",
+                            "seconds": 630
+                        }
+                    }
+                })
+            })
+            .collect();
 
         let response = json!(handle_request(
-            &RequestBuilder::delete(format!("/teams/{}/sanctions/{}", team_id, sanction_id)),
-            &DbMock::default(),
+            &RequestBuilder::post(format!("/teams/{}/sanctions", team_id), &json!(sanctions)),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(rules),
+                ..Default::default()
+            },
         )
         .unwrap());
 
-        assert_eq!(response["team_id"], json!(team_id));
-        assert_eq!(response["id"], json!(sanction_id));
+        assert_eq!(response[0]["price"], json!(550));
+        assert_eq!(response[1]["price"], json!(500));
+        assert_eq!(response[2]["price"], json!(550));
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([{
+                    "user_id": Uuid::new_v4(),
+                    "sanction_info": {
+                        "associated_rule": Uuid::default(),
+                        "extra_info": {
+                            "type": "DURATION",
+                            "seconds": 630
+                        }
+                    }
+                }]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![Rule::default()]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+    }
+
+    #[test]
+    fn test_create_escalating_sanctions() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Escalating {
+                tiers: vec![100, 200, 500],
+                reset_period: ResetPeriod::Month,
+            },
+            ..Default::default()
+        };
+
+        let sanction = json!({
+            "user_id": user_id,
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "NONE"
+                }
+            }
+        });
+        let other_user_sanction = json!({
+            "user_id": Uuid::new_v4(),
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "NONE"
+                }
+            }
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([sanction, other_user_sanction, sanction, sanction]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    sanction_info: SanctionInfo {
+                        associated_rule: rule.id,
+                        ..Default::default()
+                    },
+                    created_at: Local::today().naive_local(),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["price"], json!(200));
+        assert_eq!(response[1]["price"], json!(100));
+        assert_eq!(response[2]["price"], json!(500));
+        assert_eq!(response[3]["price"], json!(500));
+    }
+
+    #[test]
+    fn test_create_sanctions_with_limits() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let capped_rule = Rule {
+            kind: RuleKind::Multiplication {
+                price_to_multiply: 300,
+            },
+            limits: PriceLimits {
+                min_price: Some(200),
+                max_price: Some(2000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let period_capped_rule = Rule {
+            id: Uuid::new_v4(),
+            kind: RuleKind::Basic { price: 1000 },
+            limits: PriceLimits {
+                period_cap: Some(PeriodCap {
+                    max_price: 2500,
+                    period: ResetPeriod::Month,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let period_floored_rule = Rule {
+            id: Uuid::new_v4(),
+            kind: RuleKind::Basic { price: 300 },
+            limits: PriceLimits {
+                period_floor: Some(PeriodFloor {
+                    min_price: 1000,
+                    period: ResetPeriod::Month,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let sanction = |rule: &Rule, extra_info: serde_json::Value| {
+            json!({
+                "user_id": user_id,
+                "sanction_info": {
+                    "associated_rule": rule.id,
+                    "extra_info": extra_info
+                }
+            })
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([
+                    sanction(
+                        &capped_rule,
+                        json!({ "type": "MULTIPLICATION", "factor": 10 })
+                    ),
+                    sanction(
+                        &capped_rule,
+                        json!({ "type": "MULTIPLICATION", "factor": 0 })
+                    ),
+                    sanction(
+                        &capped_rule,
+                        json!({ "type": "MULTIPLICATION", "factor": 5 })
+                    ),
+                    sanction(&period_capped_rule, json!({ "type": "NONE" })),
+                    sanction(&period_capped_rule, json!({ "type": "NONE" })),
+                    sanction(&period_floored_rule, json!({ "type": "NONE" })),
+                    sanction(&period_floored_rule, json!({ "type": "NONE" })),
+                ]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![
+                    capped_rule.clone(),
+                    period_capped_rule.clone(),
+                    period_floored_rule.clone(),
+                ]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    sanction_info: SanctionInfo {
+                        associated_rule: period_capped_rule.id,
+                        ..Default::default()
+                    },
+                    price: 1000,
+                    created_at: Local::today().naive_local(),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["price"], json!(2000));
+        assert_eq!(response[0]["applied_limit"], json!("MAX_PRICE"));
+        assert_eq!(response[1]["price"], json!(200));
+        assert_eq!(response[1]["applied_limit"], json!("MIN_PRICE"));
+        assert_eq!(response[2]["price"], json!(1500));
+        assert_eq!(response[2]["applied_limit"], serde_json::Value::Null);
+        assert_eq!(response[3]["price"], json!(1000));
+        assert_eq!(response[4]["price"], json!(500));
+        assert_eq!(response[4]["applied_limit"], json!("PERIOD_CAP"));
+        assert_eq!(response[5]["price"], json!(1000));
+        assert_eq!(response[5]["applied_limit"], json!("PERIOD_FLOOR"));
+        assert_eq!(response[6]["price"], json!(300));
+        assert_eq!(response[6]["applied_limit"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_create_monthly_sanction() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Monthly { price: 1000 },
+            ..Default::default()
+        };
+
+        let sanction = json!({
+            "user_id": user_id,
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "NONE"
+                }
+            }
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(format!("/teams/{}/sanctions", team_id), &json!([sanction])),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["price"], json!(1000));
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions", team_id),
+                &json!([sanction, sanction]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::DuplicatedField);
+
+        let error = handle_request(
+            &RequestBuilder::post(format!("/teams/{}/sanctions", team_id), &json!([sanction])),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    sanction_info: SanctionInfo {
+                        associated_rule: rule.id,
+                        ..Default::default()
+                    },
+                    created_at: Local::today().naive_local(),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::DuplicatedField);
+    }
+
+    #[test]
+    fn test_generate_monthly_sanctions() {
+        let team_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Monthly { price: 1000 },
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/monthly?month=10&year=2019", team_id),
+                &json!({})
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response.as_array().unwrap().len(), 1);
+        assert_eq!(
+            response[0]["created_at"],
+            json!(NaiveDate::from_ymd(2019, 10, 1))
+        );
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/monthly?month=10", team_id),
+                &json!({}),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadParameter);
+    }
+
+    #[test]
+    fn test_update_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Multiplication {
+                price_to_multiply: 350,
+            },
+            ..Default::default()
+        };
+
+        let sanction = json!({
+            "user_id": user_id,
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "MULTIPLICATION",
+                    "factor": 3
+                }
+            }
+        });
+
+        let db = DbMock {
+            teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/{}", team_id, sanction_id),
+                &sanction
+            ),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(sanction_id));
+        assert_eq!(response["user_id"], json!(user_id));
+        assert_eq!(response["price"], json!(1050));
+        assert_eq!(
+            response["created_at"],
+            json!(Sanction::default().created_at)
+        );
+
+        let response = json!(handle_request(
+            &RequestBuilder::patch(
+                format!("/teams/{}/sanctions/{}", team_id, sanction_id),
+                &sanction
+            ),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["price"], json!(1050));
+    }
+
+    #[test]
+    fn test_update_sanction_fails() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Basic { price: 100 },
+            ..Default::default()
+        };
+
+        let sanction = json!({
+            "user_id": user_id,
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "NONE"
+                }
+            }
+        });
+        let url = format!("/teams/{}/sanctions/{}", team_id, sanction_id);
+
+        let error = handle_request(
+            &RequestBuilder::post(url.clone(), &sanction),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+
+        let error = handle_request(
+            &RequestBuilder::post(url.clone(), &sanction),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadReference);
+
+        let error = handle_request(
+            &RequestBuilder::post(url, &sanction),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    ..Default::default()
+                })),
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                    sanction_id,
+                    amount: 200,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            format!(
+                "The price of the sanction {} can't be lower than the 200 already paid",
+                sanction_id
+            )
+        );
+    }
+
+    #[test]
+    fn test_get_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let rule = Rule {
+            id: Uuid::new_v4(),
+            name: String::from("Retard"),
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/sanctions/{}", team_id, sanction_id)),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    rule_version_id: Some(rule.id),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(sanction_id));
+        assert_eq!(response["rule_version_id"], json!(rule.id));
+        assert_eq!(response["rule_version"]["rule_id"], json!(rule.id));
+        assert_eq!(response["rule_version"]["name"], json!("Retard"));
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/sanctions/{}", team_id, sanction_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert!(response["rule_version"].is_null());
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/sanctions/{}", team_id, sanction_id)),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_delete_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/sanctions/{}", team_id, sanction_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["team_id"], json!(team_id));
+        assert_eq!(response["id"], json!(sanction_id));
+        assert!(!response["deleted_at"].is_null());
+    }
+
+    #[test]
+    fn test_restore_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/{}/restore", team_id, sanction_id),
+                &json!({})
+            ),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(sanction_id));
+        assert_eq!(response["deleted_at"], json!(null));
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/{}/restore", team_id, sanction_id),
+                &json!({}),
+            ),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_contest_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let url = format!("/teams/{}/sanctions/{}/contest", team_id, sanction_id);
+        let db = DbMock {
+            sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                user_id,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let actor = Actor {
+            kind: ActorKind::User,
+            id: Some(user_id),
+        };
+
+        let response = json!(handle_actor_request(
+            &RequestBuilder::post(url.clone(), &json!({ "reason": "I was on time" })),
+            &db,
+            actor,
+        )
+        .unwrap());
+
+        assert_eq!(response["appeal_status"], json!("CONTESTED"));
+        assert_eq!(response["appeal_reason"], json!("I was on time"));
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(url.clone(), &json!({ "reason": "I was on time" })),
+            &db,
+            Actor {
+                id: Some(Uuid::new_v4()),
+                ..actor
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Forbidden);
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(url, &json!({ "reason": " " })),
+            &db,
+            actor,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+    }
+
+    #[test]
+    fn test_decide_appeal() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let url = format!("/teams/{}/sanctions/{}/appeal", team_id, sanction_id);
+        let db = DbMock {
+            sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                price: 500,
+                appeal_status: Some(AppealStatus::Contested),
+                appeal_reason: Some(String::from("I was on time")),
+                ..Default::default()
+            })),
+            payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                sanction_id,
+                amount: 100,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        let response = json!(handle_actor_request(
+            &RequestBuilder::post(
+                url.clone(),
+                &json!({ "decision": "REDUCE", "price": 200, "comment": "Only 5 minutes late" })
+            ),
+            &db,
+            Actor::anonymous(),
+        )
+        .unwrap());
+
+        assert_eq!(response["price"], json!(200));
+        assert_eq!(response["appeal_status"], json!("REDUCED"));
+        assert_eq!(response["appeal_reason"], json!("I was on time"));
+        assert_eq!(response["appeal_comment"], json!("Only 5 minutes late"));
+
+        let response = json!(handle_actor_request(
+            &RequestBuilder::post(url.clone(), &json!({ "decision": "REJECT" })),
+            &db,
+            Actor::anonymous(),
+        )
+        .unwrap());
+
+        assert_eq!(response["price"], json!(500));
+        assert_eq!(response["appeal_status"], json!("REJECTED"));
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(url.clone(), &json!({ "decision": "CANCEL" })),
+            &db,
+            Actor::anonymous(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.description,
+            format!(
+                "The price of the sanction {} can't be lower than the 100 already paid",
+                sanction_id
+            )
+        );
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(url, &json!({ "decision": "REDUCE", "price": 600 })),
+            &db,
+            Actor::anonymous(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+    }
+
+    #[test]
+    fn test_decide_appeal_fails_if_not_contested() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/{}/appeal", team_id, sanction_id),
+                &json!({ "decision": "CANCEL" }),
+            ),
+            &DbMock::default(),
+            Actor::anonymous(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            format!("The sanction {} isn't contested", sanction_id)
+        );
+    }
+
+    #[test]
+    fn test_propose_sanctions() {
+        let team_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Multiplication {
+                price_to_multiply: 350,
+            },
+            ..Default::default()
+        };
+
+        let proposals = json!([{
+            "user_id": Uuid::new_v4(),
+            "sanction_info": {
+                "associated_rule": rule.id,
+                "extra_info": {
+                    "type": "MULTIPLICATION",
+                    "factor": 2
+                }
+            }
+        }]);
+
+        let response = json!(handle_actor_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/proposals", team_id),
+                &proposals
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                ..Default::default()
+            },
+            Actor {
+                kind: ActorKind::User,
+                id: Some(user_id),
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["price"], json!(700));
+        assert_eq!(response[0]["approval_status"], json!("PENDING"));
+        assert_eq!(response[0]["proposed_by"], json!(user_id));
+    }
+
+    #[test]
+    fn test_propose_monthly_sanction_fails() {
+        let team_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Monthly { price: 1000 },
+            ..Default::default()
+        };
+
+        let error = handle_actor_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/proposals", team_id),
+                &json!([{
+                    "user_id": Uuid::new_v4(),
+                    "sanction_info": {
+                        "associated_rule": rule.id,
+                        "extra_info": {
+                            "type": "NONE"
+                        }
+                    }
+                }]),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                ..Default::default()
+            },
+            Actor::anonymous(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+    }
+
+    #[test]
+    fn test_approve_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+
+        let rule = Rule {
+            kind: RuleKind::Multiplication {
+                price_to_multiply: 350,
+            },
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/sanctions/{}/approve", team_id, sanction_id),
+                &json!({}),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    sanction_info: SanctionInfo {
+                        associated_rule: rule.id,
+                        extra_info: ExtraInfo::Multiplication { factor: 3 },
+                    },
+                    price: 700,
+                    approval_status: ApprovalStatus::Pending,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["price"], json!(1050));
+        assert_eq!(response["approval_status"], json!("APPROVED"));
+    }
+
+    #[test]
+    fn test_reject_sanction() {
+        let team_id = Uuid::new_v4();
+        let sanction_id = Uuid::new_v4();
+        let url = format!("/teams/{}/sanctions/{}/reject", team_id, sanction_id);
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(url.clone(), &json!({})),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    price: 700,
+                    approval_status: ApprovalStatus::Pending,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["price"], json!(700));
+        assert_eq!(response["approval_status"], json!("REJECTED"));
+
+        let error =
+            handle_request(&RequestBuilder::post(url, &json!({})), &DbMock::default()).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            format!("The sanction {} isn't pending", sanction_id)
+        );
     }
 
     #[test]
@@ -2,6 +2,8 @@ pub mod parameters {
     use chrono::NaiveDate;
     use rouille::Request;
 
+    use super::monthly::month_interval;
+
     #[derive(Debug)]
     pub struct ParameterError {
         pub parameter_name: String,
@@ -26,16 +28,19 @@ pub mod parameters {
     pub struct ParametersHandler {
         format: Option<FormatParameter>,
         year_and_month: Option<(YearParameter, MonthParameter)>,
+        include_deleted: Option<IncludeDeletedParameter>,
     }
 
     impl ParametersHandler {
         pub fn from_request(request: &Request) -> Result<ParametersHandler, ParameterError> {
             let format = FormatParameter::from_request(request)?;
             let year_and_month = Self::extract_year_and_month(request)?;
+            let include_deleted = IncludeDeletedParameter::from_request(request)?;
 
             Ok(ParametersHandler {
                 format,
                 year_and_month,
+                include_deleted,
             })
         }
 
@@ -64,18 +69,9 @@ pub mod parameters {
         }
 
         pub fn date_interval(&self) -> Option<(NaiveDate, NaiveDate)> {
-            match &self.year_and_month {
-                Some((year, month)) => {
-                    let year_2 = if month.0 == 12 { year.0 + 1 } else { year.0 };
-                    let month_2 = if month.0 == 12 { 1 } else { month.0 + 1 };
-
-                    Some((
-                        NaiveDate::from_ymd(year.0, month.0, 1),
-                        NaiveDate::from_ymd(year_2, month_2, 1).pred(),
-                    ))
-                }
-                None => None,
-            }
+            self.year_and_month
+                .as_ref()
+                .map(|(year, month)| month_interval(NaiveDate::from_ymd(year.0, month.0, 1)))
         }
 
         pub fn must_be_formatted(&self) -> bool {
@@ -84,6 +80,10 @@ pub mod parameters {
                 _ => false,
             }
         }
+
+        pub fn must_include_deleted(&self) -> bool {
+            matches!(self.include_deleted, Some(IncludeDeletedParameter(true)))
+        }
     }
 
     trait Parameter<T> {
@@ -184,6 +184,27 @@ pub mod parameters {
         }
     }
 
+    #[derive(Debug)]
+    struct IncludeDeletedParameter(pub bool);
+
+    impl Parameter<Self> for IncludeDeletedParameter {
+        fn from_string(string: &str) -> Result<Self, ParameterError> {
+            match string.parse::<bool>() {
+                Ok(boolean) => Ok(IncludeDeletedParameter(boolean)),
+                Err(_) => Err(ParameterError {
+                    parameter_name: Self::parameter_name(),
+                    kind: ParameterErrorKind::UnvalidType {
+                        expected_type: String::from("boolean"),
+                    },
+                }),
+            }
+        }
+
+        fn parameter_name() -> String {
+            String::from("include_deleted")
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -197,6 +218,7 @@ pub mod parameters {
             let param_handler = ParametersHandler::from_request(&request).unwrap();
 
             assert_eq!(param_handler.must_be_formatted(), true);
+            assert!(!param_handler.must_include_deleted());
             assert_eq!(
                 param_handler.date_interval(),
                 Some((
@@ -287,18 +309,47 @@ pub mod formatter {
     use std::collections::HashMap;
     use uuid::Uuid;
 
-    use super::super::models::Sanction;
+    use super::super::models::{ApprovalStatus, Sanction, SanctionWithStatus};
+    use crate::payments::models::{Payment, PaymentStatus};
 
-    pub fn map_by_users(result: Vec<Sanction>) -> HashMap<Uuid, Vec<Sanction>> {
-        let mut mapped_result = HashMap::new();
+    pub fn with_status(sanctions: Vec<Sanction>, payments: &[Payment]) -> Vec<SanctionWithStatus> {
+        let mut paid_by_sanction: HashMap<Uuid, i64> = HashMap::new();
 
-        result.into_iter().for_each(|sanction| {
-            mapped_result
-                .entry(sanction.user_id)
-                .and_modify(|v: &mut Vec<Sanction>| v.push(sanction.clone()))
-                .or_insert_with(|| vec![sanction]);
+        payments.iter().for_each(|payment| {
+            *paid_by_sanction.entry(payment.sanction_id).or_insert(0) += payment.amount;
         });
 
+        sanctions
+            .into_iter()
+            .map(|sanction| {
+                let paid = paid_by_sanction
+                    .get(&sanction.id)
+                    .cloned()
+                    .unwrap_or_default();
+
+                SanctionWithStatus {
+                    status: PaymentStatus::from_amounts(sanction.price, paid),
+                    paid,
+                    sanction,
+                }
+            })
+            .collect()
+    }
+
+    /// Only the approved sanctions are mapped, since the others aren't owed.
+    pub fn map_by_users(result: Vec<SanctionWithStatus>) -> HashMap<Uuid, Vec<SanctionWithStatus>> {
+        let mut mapped_result = HashMap::new();
+
+        result
+            .into_iter()
+            .filter(|sanction| sanction.sanction.approval_status == ApprovalStatus::Approved)
+            .for_each(|sanction| {
+                mapped_result
+                    .entry(sanction.sanction.user_id)
+                    .and_modify(|v: &mut Vec<SanctionWithStatus>| v.push(sanction.clone()))
+                    .or_insert_with(|| vec![sanction]);
+            });
+
         mapped_result
     }
 
@@ -318,11 +369,58 @@ pub mod formatter {
                     associated_rule: Uuid::new_v4(),
                     extra_info: ExtraInfo::None,
                 },
-                price: 0.0,
+                price: 0,
                 created_at: NaiveDate::from_ymd(2019, 10, 5),
+                charged_month: None,
+                applied_limit: None,
+                deleted_at: None,
+                appeal_status: None,
+                appeal_reason: None,
+                appeal_comment: None,
+                approval_status: ApprovalStatus::Approved,
+                proposed_by: None,
+                rule_id: None,
+                rule_version_id: None,
             }
         }
 
+        #[test]
+        fn test_with_status() {
+            let sanctions: Vec<Sanction> = (0..3)
+                .map(|_| Sanction {
+                    price: 400,
+                    ..create_default_sanction(Uuid::new_v4())
+                })
+                .collect();
+
+            let payments = vec![
+                Payment {
+                    sanction_id: sanctions[1].id,
+                    amount: 150,
+                    ..Default::default()
+                },
+                Payment {
+                    sanction_id: sanctions[2].id,
+                    amount: 150,
+                    ..Default::default()
+                },
+                Payment {
+                    sanction_id: sanctions[2].id,
+                    amount: 250,
+                    ..Default::default()
+                },
+            ];
+
+            let result = with_status(sanctions, &payments);
+
+            assert_eq!(result[0].paid, 0);
+            assert_eq!(result[0].status, PaymentStatus::Unpaid);
+            assert_eq!(result[1].paid, 150);
+            assert_eq!(result[1].status, PaymentStatus::PartiallyPaid);
+            assert_eq!(result[2].paid, 400);
+            assert_eq!(result[2].status, PaymentStatus::Paid);
+        }
+
         #[test]
         fn test_mapping() {
             let user_1 = Uuid::new_v4();
@@ -334,12 +432,203 @@ pub mod formatter {
                 create_default_sanction(user_1),
                 create_default_sanction(user_2),
                 create_default_sanction(user_1),
+                Sanction {
+                    approval_status: ApprovalStatus::Pending,
+                    ..create_default_sanction(user_1)
+                },
             ];
 
-            let mapped_users = map_by_users(result);
+            let mapped_users = map_by_users(with_status(result, &[]));
 
             assert_eq!(mapped_users.get(&user_1).unwrap().len(), 3);
             assert_eq!(mapped_users.get(&user_2).unwrap().len(), 2);
         }
     }
 }
+
+pub mod monthly {
+    use chrono::{Datelike, NaiveDate};
+    use uuid::Uuid;
+
+    use super::super::{
+        interface::SanctionsDb,
+        models::{
+            ApprovalStatus, CreateSanction, ExtraInfo, PriceError, PricingContext, Sanction,
+            SanctionInfo,
+        },
+    };
+    use crate::teams::{interface::TeamsDb, models::RuleKind};
+    use crate::users::interface::UsersDb;
+
+    /// Returns the first and the last day of the month of `date`.
+    pub fn month_interval(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let next_month = match date.month() {
+            12 => NaiveDate::from_ymd(date.year() + 1, 1, 1),
+            month => NaiveDate::from_ymd(date.year(), month + 1, 1),
+        };
+
+        (date.with_day(1).unwrap(), next_month.pred())
+    }
+
+    pub fn is_charged(sanctions: &[Sanction], user_id: Uuid, rule_id: Uuid) -> bool {
+        sanctions.iter().any(|sanction| {
+            sanction.user_id == user_id
+                && sanction.sanction_info.associated_rule == rule_id
+                && sanction.approval_status == ApprovalStatus::Approved
+        })
+    }
+
+    /// Prices the charges of every active monthly rule of the team to every
+    /// active user who hasn't been charged yet for the month of `date`, so it
+    /// can safely be run again. The charges are priced like any other sanction,
+    /// within the limits of their rule, and are left to be created.
+    pub fn generate_charges<T>(
+        db: &T,
+        team_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<Vec<CreateSanction>, PriceError>
+    where
+        T: SanctionsDb + TeamsDb + UsersDb,
+    {
+        let interval = month_interval(date);
+
+        let team = db.get_team(team_id)?.without_archived_rules();
+        let users = db.get_users(team_id, false)?;
+        let charged = &db.get_sanctions(team_id, Some(interval), false)?;
+
+        let mut sanctions: Vec<CreateSanction> = vec![];
+
+        for rule in team.rules.iter() {
+            if let RuleKind::Monthly { .. } = rule.kind {
+                for user in users.iter() {
+                    if is_charged(charged, user.id, rule.id) {
+                        continue;
+                    }
+
+                    let sanction_info = SanctionInfo {
+                        associated_rule: rule.id,
+                        extra_info: ExtraInfo::None,
+                    };
+                    let price = sanction_info.get_price(
+                        rule.clone(),
+                        &PricingContext {
+                            db,
+                            team_id,
+                            user_id: user.id,
+                            created_at: interval.0,
+                            pending: &sanctions,
+                            replaced: None,
+                        },
+                    )?;
+
+                    sanctions.push(CreateSanction {
+                        id: Uuid::new_v4(),
+                        user_id: user.id,
+                        team_id,
+                        sanction_info,
+                        price: price.value,
+                        created_at: Some(interval.0),
+                        applied_limit: price.applied_limit,
+                        approval_status: ApprovalStatus::Approved,
+                        proposed_by: None,
+                    });
+                }
+            }
+        }
+
+        Ok(sanctions)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::sanctions::models::PriceLimit;
+        use crate::teams::models::{PriceLimits, Rule};
+        use crate::test_utils::routes::{DbMock, TeamsDbMock};
+
+        #[test]
+        fn test_month_interval() {
+            assert_eq!(
+                month_interval(NaiveDate::from_ymd(2019, 2, 14)),
+                (
+                    NaiveDate::from_ymd(2019, 2, 1),
+                    NaiveDate::from_ymd(2019, 2, 28)
+                )
+            );
+            assert_eq!(
+                month_interval(NaiveDate::from_ymd(2019, 12, 31)),
+                (
+                    NaiveDate::from_ymd(2019, 12, 1),
+                    NaiveDate::from_ymd(2019, 12, 31)
+                )
+            );
+        }
+
+        #[test]
+        fn test_generate_charges() {
+            let team_id = Uuid::new_v4();
+            let rule = Rule {
+                kind: RuleKind::Monthly { price: 1000 },
+                ..Default::default()
+            };
+
+            let sanctions = generate_charges(
+                &DbMock {
+                    teams_db: TeamsDbMock::SuccessWithRules(vec![
+                        rule.clone(),
+                        Rule {
+                            id: Uuid::new_v4(),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                team_id,
+                NaiveDate::from_ymd(2019, 10, 20),
+            )
+            .unwrap();
+
+            assert_eq!(sanctions.len(), 1);
+            assert_eq!(sanctions[0].sanction_info.associated_rule, rule.id);
+            assert_eq!(sanctions[0].price, 1000);
+            assert_eq!(
+                sanctions[0].created_at,
+                Some(NaiveDate::from_ymd(2019, 10, 1))
+            );
+
+            let sanctions = generate_charges(
+                &DbMock::default(),
+                team_id,
+                NaiveDate::from_ymd(2019, 10, 20),
+            )
+            .unwrap();
+
+            assert!(sanctions.is_empty());
+        }
+
+        #[test]
+        fn test_generate_charges_within_limits() {
+            let rule = Rule {
+                kind: RuleKind::Monthly { price: 1000 },
+                limits: PriceLimits {
+                    max_price: Some(800),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let sanctions = generate_charges(
+                &DbMock {
+                    teams_db: TeamsDbMock::SuccessWithRules(vec![rule]),
+                    ..Default::default()
+                },
+                Uuid::new_v4(),
+                NaiveDate::from_ymd(2019, 10, 20),
+            )
+            .unwrap();
+
+            assert_eq!(sanctions[0].price, 800);
+            assert_eq!(sanctions[0].applied_limit, Some(PriceLimit::MaxPrice));
+        }
+    }
+}
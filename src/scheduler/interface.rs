@@ -0,0 +1,10 @@
+use uuid::Uuid;
+
+use super::models::MonthlyRun;
+use crate::database::postgres::DbError;
+
+pub trait MonthlyRunsDb {
+    fn get_monthly_runs(&self, team_id: Uuid) -> Result<Vec<MonthlyRun>, DbError>;
+
+    fn create_monthly_run(&self, run: &MonthlyRun) -> Result<MonthlyRun, DbError>;
+}
@@ -0,0 +1,37 @@
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Utc,
+};
+use diesel::{Insertable, Queryable};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::database::schema::monthly_runs;
+
+/// Records that the monthly rules of a team have been charged for `month`,
+/// which is always the first day of the charged month.
+#[derive(Debug, Clone, Queryable, Insertable, PartialEq, Serialize)]
+#[table_name = "monthly_runs"]
+pub struct MonthlyRun {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub month: NaiveDate,
+    pub ran_at: NaiveDateTime,
+}
+
+impl MonthlyRun {
+    pub fn new(team_id: Uuid, month: NaiveDate) -> MonthlyRun {
+        MonthlyRun {
+            id: Uuid::new_v4(),
+            team_id,
+            month,
+            ran_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl Default for MonthlyRun {
+    fn default() -> MonthlyRun {
+        MonthlyRun::new(Uuid::default(), NaiveDate::from_ymd(2019, 10, 1))
+    }
+}
@@ -0,0 +1,82 @@
+use diesel::prelude::*;
+use std::ops::Deref;
+use uuid::Uuid;
+
+use super::{interface::MonthlyRunsDb, models::MonthlyRun};
+use crate::database::{
+    postgres::{DbConnection, DbError},
+    schema::monthly_runs,
+};
+
+impl MonthlyRunsDb for DbConnection {
+    fn get_monthly_runs(&self, team_id: Uuid) -> Result<Vec<MonthlyRun>, DbError> {
+        let runs: Vec<MonthlyRun> = monthly_runs::table
+            .filter(monthly_runs::team_id.eq(team_id))
+            .order(monthly_runs::month.desc())
+            .get_results(self.deref())?;
+
+        Ok(runs)
+    }
+
+    fn create_monthly_run(&self, run: &MonthlyRun) -> Result<MonthlyRun, DbError> {
+        let run: MonthlyRun = diesel::insert_into(monthly_runs::table)
+            .values(run)
+            .get_result(self.deref())?;
+
+        Ok(run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use diesel::result::Error;
+
+    use super::*;
+    use crate::teams::{interface::TeamsDb, models::Team};
+    use crate::test_utils::postgres::init_connection;
+
+    #[test]
+    fn test_monthly_runs() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+
+            let october = conn
+                .create_monthly_run(&MonthlyRun::new(team_id, NaiveDate::from_ymd(2019, 10, 1)))
+                .unwrap();
+            let november = conn
+                .create_monthly_run(&MonthlyRun::new(team_id, NaiveDate::from_ymd(2019, 11, 1)))
+                .unwrap();
+
+            let runs = conn.get_monthly_runs(team_id).unwrap();
+
+            assert_eq!(runs.len(), 2);
+            assert_eq!(runs[0].id, november.id);
+            assert_eq!(runs[1].id, october.id);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_create_monthly_run_twice_fails() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let month = NaiveDate::from_ymd(2019, 10, 1);
+
+            conn.create_monthly_run(&MonthlyRun::new(team_id, month))
+                .unwrap();
+            let error = conn
+                .create_monthly_run(&MonthlyRun::new(team_id, month))
+                .unwrap_err();
+
+            assert!(matches!(error, DbError::UniqueViolation(_)));
+
+            Ok(())
+        });
+    }
+}
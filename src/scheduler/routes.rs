@@ -0,0 +1,56 @@
+use rouille::{router, Request};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{interface::MonthlyRunsDb, models::MonthlyRun};
+use crate::api::models::ErrorResponse;
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum ResultWrapper {
+    MonthlyRuns(Vec<MonthlyRun>),
+}
+
+pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResultWrapper, ErrorResponse>
+where
+    T: MonthlyRunsDb,
+{
+    router!(request,
+        (GET) (/teams/{team_id: Uuid}/monthly_runs) => {
+            let result = db.get_monthly_runs(team_id)?;
+
+            Ok(ResultWrapper::MonthlyRuns(result))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::naive::NaiveDate;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::models::test_utils::RequestBuilder;
+    use crate::test_utils::routes::{DbMock, MonthlyRunsDbMock};
+
+    #[test]
+    fn test_get_monthly_runs() {
+        let team_id = Uuid::new_v4();
+        let month = NaiveDate::from_ymd(2019, 10, 1);
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/monthly_runs", team_id)),
+            &DbMock {
+                monthly_runs_db: MonthlyRunsDbMock::SuccessWithRun(month),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["team_id"], json!(team_id));
+        assert_eq!(response[0]["month"], json!(month));
+    }
+}
@@ -0,0 +1,173 @@
+pub mod runner {
+    use chrono::naive::NaiveDate;
+    use log::error;
+    use uuid::Uuid;
+
+    use super::super::{interface::MonthlyRunsDb, models::MonthlyRun};
+    use crate::audit::interface::AuditDb;
+    use crate::sanctions::{
+        interface::SanctionsDb,
+        models::{PriceError, Sanction},
+        utils::monthly::{generate_charges, month_interval},
+    };
+    use crate::teams::interface::TeamsDb;
+    use crate::users::interface::UsersDb;
+
+    /// Charges the monthly rules of a team for the month of `date` and records
+    /// the run in the same transaction. Users already charged for this month
+    /// are skipped, so a run can be triggered again without charging anyone
+    /// twice.
+    pub fn run_month<T>(
+        db: &T,
+        team_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<(MonthlyRun, Vec<Sanction>), PriceError>
+    where
+        T: AuditDb + MonthlyRunsDb + SanctionsDb + TeamsDb + UsersDb,
+    {
+        let (month, _) = month_interval(date);
+
+        let charges = generate_charges(db, team_id, month)?;
+
+        let result = db.transaction(|| {
+            let sanctions = match charges.is_empty() {
+                true => vec![],
+                false => db.create_sanctions(&charges)?,
+            };
+
+            let run = match db
+                .get_monthly_runs(team_id)?
+                .into_iter()
+                .find(|run| run.month == month)
+            {
+                Some(run) => run,
+                None => db.create_monthly_run(&MonthlyRun::new(team_id, month))?,
+            };
+
+            Ok((run, sanctions))
+        })?;
+
+        Ok(result)
+    }
+
+    /// Runs every month of the team which hasn't been run yet, from its first
+    /// run up to the month of `date`, and returns the number of months charged.
+    /// A team which has never been run starts with the month of `date`.
+    fn run_missing_months_of_team<T>(
+        db: &T,
+        team_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<usize, PriceError>
+    where
+        T: AuditDb + MonthlyRunsDb + SanctionsDb + TeamsDb + UsersDb,
+    {
+        let (current_month, _) = month_interval(date);
+        let runs = db.get_monthly_runs(team_id)?;
+
+        let mut month = runs
+            .iter()
+            .map(|run| run.month)
+            .min()
+            .unwrap_or(current_month);
+        let mut charged_months = 0;
+
+        while month <= current_month {
+            if !runs.iter().any(|run| run.month == month) {
+                run_month(db, team_id, month)?;
+                charged_months += 1;
+            }
+
+            month = month_interval(month).1.succ();
+        }
+
+        Ok(charged_months)
+    }
+
+    /// Runs the missing months of every team up to the month of `date`, so the
+    /// months missed while the scheduler wasn't running are caught up, and
+    /// returns the number of months charged. A team which fails to be charged
+    /// is logged and left for the next run, without stopping the other teams.
+    pub fn run_missing_months<T>(db: &T, date: NaiveDate) -> Result<usize, PriceError>
+    where
+        T: AuditDb + MonthlyRunsDb + SanctionsDb + TeamsDb + UsersDb,
+    {
+        let mut charged_months = 0;
+
+        for team_id in db.get_team_ids()? {
+            match run_missing_months_of_team(db, team_id, date) {
+                Ok(months) => charged_months += months,
+                Err(err) => error!(
+                    "Failed to charge monthly rules of the team {}: {:?}",
+                    team_id, err
+                ),
+            }
+        }
+
+        Ok(charged_months)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_utils::routes::{DbMock, MonthlyRunsDbMock, TeamsDbMock};
+
+        #[test]
+        fn test_run_month() {
+            let team_id = Uuid::new_v4();
+
+            let (run, _) = run_month(
+                &DbMock::default(),
+                team_id,
+                NaiveDate::from_ymd(2019, 10, 20),
+            )
+            .unwrap();
+
+            assert_eq!(run.team_id, team_id);
+            assert_eq!(run.month, NaiveDate::from_ymd(2019, 10, 1));
+        }
+
+        #[test]
+        fn test_run_missing_months() {
+            let date = NaiveDate::from_ymd(2019, 12, 10);
+
+            assert_eq!(run_missing_months(&DbMock::default(), date).unwrap(), 1);
+            assert_eq!(
+                run_missing_months(
+                    &DbMock {
+                        monthly_runs_db: MonthlyRunsDbMock::SuccessWithRun(NaiveDate::from_ymd(
+                            2019, 10, 1
+                        )),
+                        ..Default::default()
+                    },
+                    date
+                )
+                .unwrap(),
+                2
+            );
+            assert_eq!(
+                run_missing_months(
+                    &DbMock {
+                        monthly_runs_db: MonthlyRunsDbMock::SuccessWithRun(NaiveDate::from_ymd(
+                            2019, 12, 1
+                        )),
+                        ..Default::default()
+                    },
+                    date
+                )
+                .unwrap(),
+                0
+            );
+            assert_eq!(
+                run_missing_months(
+                    &DbMock {
+                        teams_db: TeamsDbMock::NotFound,
+                        ..Default::default()
+                    },
+                    date
+                )
+                .unwrap(),
+                0
+            );
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use uuid::Uuid;
+
+use super::models::Session;
+use crate::database::postgres::DbError;
+
+pub trait SessionsDb {
+    fn get_session(&self, id: Uuid) -> Result<Session, DbError>;
+
+    fn create_session(&self, session: &Session) -> Result<Session, DbError>;
+
+    fn delete_session(&self, id: Uuid) -> Result<Session, DbError>;
+}
@@ -0,0 +1,5 @@
+pub mod interface;
+pub mod models;
+pub mod postgres;
+pub mod routes;
+pub mod utils;
@@ -0,0 +1,87 @@
+use chrono::{naive::NaiveDateTime, Duration, Utc};
+use diesel::{sql_types::Varchar, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::schema::sessions;
+use crate::varchar_enum;
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub name: String,
+    pub admin_password: Option<String>,
+    pub email: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LoginResponse {
+    pub id: Uuid,
+    pub token: String,
+    pub scope: Scope,
+    pub expires_at: NaiveDateTime,
+    pub user_id: Option<Uuid>,
+}
+
+impl From<(Session, String)> for LoginResponse {
+    fn from((session, token): (Session, String)) -> LoginResponse {
+        LoginResponse {
+            id: session.team_id,
+            token,
+            scope: session.scope,
+            expires_at: session.expires_at,
+            user_id: session.user_id,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionConfig {
+    pub secret: String,
+    pub lifetime: Duration,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, PartialEq, Serialize, Deserialize)]
+#[table_name = "sessions"]
+pub struct Session {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub scope: Scope,
+    pub expires_at: NaiveDateTime,
+    pub user_id: Option<Uuid>,
+}
+
+impl Session {
+    pub fn new(team_id: Uuid, scope: Scope, lifetime: Duration) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            team_id,
+            scope,
+            expires_at: Utc::now().naive_utc() + lifetime,
+            user_id: None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now().naive_utc()
+    }
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new(Uuid::default(), Scope::ReadOnly, Duration::hours(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum Scope {
+    Admin,
+    ReadOnly,
+    /// The session acts on behalf of `Session.user_id`, with the permissions of
+    /// its role.
+    User,
+}
+
+varchar_enum!(Scope);
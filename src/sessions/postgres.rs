@@ -0,0 +1,115 @@
+use diesel::prelude::*;
+use std::ops::Deref;
+use uuid::Uuid;
+
+use super::{interface::SessionsDb, models::Session};
+use crate::database::{
+    postgres::{DbConnection, DbError},
+    schema::sessions,
+};
+
+impl SessionsDb for DbConnection {
+    fn get_session(&self, id: Uuid) -> Result<Session, DbError> {
+        let session: Session = sessions::table.find(id).get_result(self.deref())?;
+
+        Ok(session)
+    }
+
+    fn create_session(&self, session: &Session) -> Result<Session, DbError> {
+        let session: Session = diesel::insert_into(sessions::table)
+            .values(session)
+            .get_result(self.deref())?;
+
+        Ok(session)
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<Session, DbError> {
+        let session: Session = diesel::delete(sessions::table.find(id)).get_result(self.deref())?;
+
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::result::Error;
+
+    use super::*;
+    use crate::sessions::models::Scope;
+    use crate::teams::{interface::TeamsDb, models::Team};
+    use crate::test_utils::postgres::init_connection;
+
+    #[test]
+    fn test_get_session() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let created_session = conn
+                .create_session(&Session {
+                    team_id,
+                    scope: Scope::Admin,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let session = conn.get_session(created_session.id).unwrap();
+
+            assert_eq!(session.team_id, team_id);
+            assert_eq!(session.scope, Scope::Admin);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_get_unexisting_session() {
+        let conn = init_connection();
+
+        let error = conn.get_session(Uuid::new_v4()).unwrap_err();
+
+        assert_eq!(error, DbError::NotFound);
+    }
+
+    #[test]
+    fn test_create_session_fails() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let error = conn.create_session(&Session::default()).unwrap_err();
+
+            assert_eq!(
+                error,
+                DbError::ForeignKeyViolation(String::from(
+                    "The key team_id doesn\'t refer to anything"
+                ))
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_delete_session() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let session_id = conn
+                .create_session(&Session {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let deleted_session = conn.delete_session(session_id).unwrap();
+            assert_eq!(deleted_session.id, session_id);
+
+            let error = conn.get_session(session_id).unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,357 @@
+use rouille::{input::json::json_input, router, Request};
+use serde::Serialize;
+
+use super::{
+    interface::SessionsDb,
+    models::{LoginRequest, LoginResponse, Scope, Session, SessionConfig},
+    utils::{authorization::authenticate, token::sign},
+};
+use crate::api::models::ErrorResponse;
+use crate::teams::interface::TeamsDb;
+use crate::users::interface::UsersDb;
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum ResponseWrapper {
+    Login(LoginResponse),
+    Session(Session),
+}
+
+fn open_session<T>(
+    db: &T,
+    session: Session,
+    config: &SessionConfig,
+) -> Result<LoginResponse, ErrorResponse>
+where
+    T: SessionsDb,
+{
+    let session = db.create_session(&session)?;
+    let token = sign(&session, &config.secret);
+
+    Ok((session, token).into())
+}
+
+pub fn handle_request<T>(
+    request: &Request,
+    db: &T,
+    config: &SessionConfig,
+) -> Result<ResponseWrapper, ErrorResponse>
+where
+    T: SessionsDb + TeamsDb + UsersDb,
+{
+    router!(request,
+        (POST) (/login) => {
+            let input: LoginRequest = json_input(request)?;
+
+            let session = match (&input.email, &input.password) {
+                (Some(email), Some(password)) => {
+                    let team_id = db.login(&input.name, &None)?;
+                    let user = db.login_user(team_id, email, password)?;
+
+                    Session {
+                        user_id: Some(user.id),
+                        ..Session::new(team_id, Scope::User, config.lifetime)
+                    }
+                }
+                _ => {
+                    let team_id = db.login(&input.name, &input.admin_password)?;
+
+                    let scope = match input.admin_password {
+                        Some(_) => Scope::Admin,
+                        None => Scope::ReadOnly,
+                    };
+
+                    Session::new(team_id, scope, config.lifetime)
+                }
+            };
+
+            let result = open_session(db, session, config)?;
+
+            Ok(ResponseWrapper::Login(result))
+        },
+        (POST) (/logout) => {
+            let session = authenticate(request, db, config)?;
+
+            let result = db.delete_session(session.id)?;
+
+            Ok(ResponseWrapper::Session(result))
+        },
+        (POST) (/refresh) => {
+            let session = authenticate(request, db, config)?;
+
+            let result = open_session(
+                db,
+                Session {
+                    user_id: session.user_id,
+                    ..Session::new(session.team_id, session.scope, config.lifetime)
+                },
+                config,
+            )?;
+
+            db.delete_session(session.id)?;
+
+            Ok(ResponseWrapper::Login(result))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
+    use crate::test_utils::routes::{
+        session_config, DbMock, SessionsDbMock, TeamsDbMock, UsersDbMock,
+    };
+
+    #[test]
+    fn test_login() {
+        let login_request = json!({
+            "name": "CHBC",
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap());
+
+        assert_eq!(response["scope"], json!("READ_ONLY"));
+        assert_eq!(response["admin_password"], serde_json::Value::Null);
+
+        let session = verify_token(&response);
+        assert_eq!(json!(session.team_id), response["id"]);
+    }
+
+    #[test]
+    fn test_login_with_password() {
+        let login_request = json!({
+            "name": "CHBC",
+            "admin_password": "password"
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap());
+
+        assert_eq!(response["scope"], json!("ADMIN"));
+        assert_eq!(response["admin_password"], serde_json::Value::Null);
+        assert_eq!(verify_token(&response).scope, Scope::Admin);
+    }
+
+    #[test]
+    fn test_login_as_user() {
+        let login_request = json!({
+            "name": "CHBC",
+            "email": "email@gmail.com",
+            "password": "password"
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap());
+
+        assert_eq!(response["scope"], json!("USER"));
+
+        let session = verify_token(&response);
+        assert_eq!(session.scope, Scope::User);
+        assert_eq!(json!(session.user_id), response["user_id"]);
+        assert!(session.user_id.is_some());
+    }
+
+    #[test]
+    fn test_login_as_user_fails() {
+        let login_request = json!({
+            "name": "CHBC",
+            "email": "email@gmail.com",
+            "password": "password"
+        });
+
+        let error = handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock {
+                users_db: UsersDbMock::NotFound,
+                ..Default::default()
+            },
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_login_fails() {
+        let mut login_request = json!({
+            "name": "CHBC",
+        });
+
+        let error = handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock {
+                teams_db: TeamsDbMock::NotFound,
+                ..Default::default()
+            },
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+
+        login_request["admin_password"] = json!("password");
+
+        let error = handle_request(
+            &RequestBuilder::post(String::from("/login"), &login_request),
+            &DbMock {
+                teams_db: TeamsDbMock::NotFound,
+                ..Default::default()
+            },
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_logout() {
+        let session = Session::new(Uuid::new_v4(), Scope::Admin, session_config().lifetime);
+        let token = sign(&session, &session_config().secret);
+
+        let response = json!(handle_request(
+            &RequestBuilder::post_authenticated(String::from("/logout"), &json!({}), &token),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(session.id));
+    }
+
+    #[test]
+    fn test_logout_fails() {
+        let error = handle_request(
+            &RequestBuilder::post(String::from("/logout"), &json!({})),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+
+        let token = sign(&Session::default(), &session_config().secret);
+
+        let error = handle_request(
+            &RequestBuilder::post_authenticated(String::from("/logout"), &json!({}), &token),
+            &DbMock {
+                sessions_db: SessionsDbMock::NotFound,
+                ..Default::default()
+            },
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let session = Session {
+            user_id: Some(Uuid::new_v4()),
+            ..Session::new(Uuid::new_v4(), Scope::User, session_config().lifetime)
+        };
+        let token = sign(&session, &session_config().secret);
+
+        let response = json!(handle_request(
+            &RequestBuilder::post_authenticated(String::from("/refresh"), &json!({}), &token),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap());
+
+        let refreshed_session = verify_token(&response);
+
+        assert_ne!(refreshed_session.id, session.id);
+        assert_eq!(refreshed_session.team_id, session.team_id);
+        assert_eq!(refreshed_session.scope, session.scope);
+        assert_eq!(refreshed_session.user_id, session.user_id);
+    }
+
+    #[test]
+    fn test_refresh_fails() {
+        let session = Session {
+            expires_at: Session::default().expires_at - chrono::Duration::days(1),
+            ..Default::default()
+        };
+        let token = sign(&session, &session_config().secret);
+
+        let error = handle_request(
+            &RequestBuilder::post_authenticated(String::from("/refresh"), &json!({}), &token),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+        assert_eq!(error.description, String::from("The token has expired"));
+
+        let error = handle_request(
+            &RequestBuilder::post_authenticated(
+                String::from("/refresh"),
+                &json!({}),
+                &sign(&Session::default(), "other_secret"),
+            ),
+            &DbMock::default(),
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+        assert_eq!(error.description, String::from("The token is not valid"));
+
+        let session = Session {
+            user_id: Some(Uuid::new_v4()),
+            ..Session::new(Uuid::new_v4(), Scope::User, session_config().lifetime)
+        };
+
+        let error = handle_request(
+            &RequestBuilder::post_authenticated(
+                String::from("/refresh"),
+                &json!({}),
+                &sign(&session, &session_config().secret),
+            ),
+            &DbMock {
+                users_db: UsersDbMock::Deactivated,
+                ..Default::default()
+            },
+            &session_config(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+        assert_eq!(
+            error.description,
+            String::from("The user of this session has been deactivated")
+        );
+    }
+
+    fn verify_token(response: &serde_json::Value) -> Session {
+        super::super::utils::token::verify(
+            response["token"].as_str().unwrap(),
+            &session_config().secret,
+        )
+        .unwrap()
+    }
+}
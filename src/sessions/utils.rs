@@ -0,0 +1,176 @@
+pub mod token {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::super::models::Session;
+
+    type HmacSha256 = Hmac<Sha256>;
+
+    #[derive(Debug, PartialEq)]
+    pub enum TokenError {
+        Malformed,
+        BadSignature,
+    }
+
+    fn mac(secret: &str, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// A token is the base64 encoded session followed by its HMAC-SHA256
+    /// signature, separated by a dot.
+    pub fn sign(session: &Session, secret: &str) -> String {
+        let payload = base64::encode_config(
+            serde_json::to_vec(session).expect("Failed to serialize session"),
+            base64::URL_SAFE_NO_PAD,
+        );
+        let signature = base64::encode_config(
+            mac(secret, &payload).finalize().into_bytes(),
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        format!("{}.{}", payload, signature)
+    }
+
+    pub fn verify(token: &str, secret: &str) -> Result<Session, TokenError> {
+        let mut parts = token.splitn(2, '.');
+
+        let (payload, signature) = match (parts.next(), parts.next()) {
+            (Some(payload), Some(signature)) => (payload, signature),
+            _ => return Err(TokenError::Malformed),
+        };
+
+        let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| TokenError::Malformed)?;
+
+        mac(secret, payload)
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| TokenError::Malformed)?;
+
+        serde_json::from_slice(&payload).map_err(|_| TokenError::Malformed)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_verify_signed_token() {
+            let session = Session::default();
+
+            let token = sign(&session, "secret");
+
+            assert_eq!(verify(&token, "secret").unwrap(), session);
+        }
+
+        #[test]
+        fn test_verify_fails() {
+            let token = sign(&Session::default(), "secret");
+
+            assert_eq!(
+                verify(&token, "other_secret").unwrap_err(),
+                TokenError::BadSignature
+            );
+
+            let forged_payload = base64::encode_config(
+                serde_json::to_vec(&Session::default()).unwrap(),
+                base64::URL_SAFE_NO_PAD,
+            );
+            let signature = token.split('.').nth(1).unwrap();
+
+            assert_eq!(
+                verify(&format!("{}.{}", forged_payload, signature), "secret").unwrap_err(),
+                TokenError::BadSignature
+            );
+            assert_eq!(
+                verify("token", "secret").unwrap_err(),
+                TokenError::Malformed
+            );
+        }
+    }
+}
+
+pub mod authorization {
+    use rouille::Request;
+
+    use super::super::{
+        interface::SessionsDb,
+        models::{Session, SessionConfig},
+    };
+    use super::token::verify;
+    use crate::api::models::ErrorResponse;
+    use crate::database::postgres::DbError;
+    use crate::users::interface::UsersDb;
+
+    /// Extracts the token of an `Authorization: Bearer <token>` header.
+    fn bearer_token(request: &Request) -> Option<&str> {
+        request
+            .header("Authorization")
+            .and_then(|header| {
+                let mut parts = header.splitn(2, ' ');
+                match (parts.next(), parts.next()) {
+                    (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                        Some(token)
+                    }
+                    _ => None,
+                }
+            })
+            .map(str::trim)
+    }
+
+    /// Returns the session carried by the bearer token of the request, as long
+    /// as its signature is valid, it hasn't expired, it hasn't been revoked and
+    /// its user, if any, is still active.
+    pub fn authenticate<T>(
+        request: &Request,
+        db: &T,
+        config: &SessionConfig,
+    ) -> Result<Session, ErrorResponse>
+    where
+        T: SessionsDb + UsersDb,
+    {
+        let token = bearer_token(request).ok_or_else(|| {
+            ErrorResponse::unauthorized(String::from("A bearer token is required"))
+        })?;
+
+        let session = verify(token, &config.secret)
+            .map_err(|_| ErrorResponse::unauthorized(String::from("The token is not valid")))?;
+
+        if session.is_expired() {
+            return Err(ErrorResponse::unauthorized(String::from(
+                "The token has expired",
+            )));
+        }
+
+        db.get_session(session.id).map_err(|err| match err {
+            DbError::NotFound => {
+                ErrorResponse::unauthorized(String::from("The session has been revoked"))
+            }
+            _ => err.into(),
+        })?;
+
+        if let Some(user_id) = session.user_id {
+            match db.get_user(session.team_id, user_id) {
+                Ok(user) if user.is_active() => {}
+                Ok(_) => {
+                    return Err(ErrorResponse::unauthorized(String::from(
+                        "The user of this session has been deactivated",
+                    )))
+                }
+                Err(DbError::NotFound) => {
+                    return Err(ErrorResponse::unauthorized(String::from(
+                        "The user of this session doesn't exist anymore",
+                    )))
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(session)
+    }
+}
@@ -1,14 +1,69 @@
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 
-use super::models::{Team, UpdateTeam};
+use super::models::{Rule, RuleCategory, RuleVersion, Team, UpdateCategory, UpdateTeam};
 use crate::database::postgres::DbError;
 
 pub trait TeamsDb {
+    /// Teams pending deletion can only be logged in with the admin password, so
+    /// that they can be restored.
     fn login(&self, name: &str, admin_password: &Option<String>) -> Result<Uuid, DbError>;
 
+    /// Leaves out the teams pending deletion.
+    fn get_team_ids(&self) -> Result<Vec<Uuid>, DbError>;
+
     fn get_team(&self, id: Uuid) -> Result<Team, DbError>;
 
+    /// The team starts with the `DEFAULT_CATEGORIES`.
     fn create_team(&self, team: &Team) -> Result<Team, DbError>;
 
+    /// The active rules which aren't given anymore are removed, unless sanctions
+    /// still refer to them, while the archived ones are kept. The currency can't
+    /// be changed anymore once the team has sanctions.
     fn update_team(&self, id: Uuid, team: &UpdateTeam) -> Result<Team, DbError>;
+
+    /// Adds, replaces or removes a single rule of the team. Any change to the
+    /// terms of a rule, here or through `update_team`, adds a new version.
+    fn create_rule(&self, team_id: Uuid, rule: &Rule) -> Result<Rule, DbError>;
+
+    fn update_rule(&self, team_id: Uuid, rule_id: Uuid, rule: &Rule) -> Result<Rule, DbError>;
+
+    /// Fails while sanctions still refer to the rule, which can then only be
+    /// archived.
+    fn delete_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError>;
+
+    fn archive_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError>;
+
+    fn restore_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError>;
+
+    /// Lists the versions of the rule, the oldest first. Once the rule is
+    /// removed, only the versions which sanctions were priced under are kept.
+    fn get_rule_versions(&self, team_id: Uuid, rule_id: Uuid) -> Result<Vec<RuleVersion>, DbError>;
+
+    fn get_rule_version(&self, team_id: Uuid, version_id: Uuid) -> Result<RuleVersion, DbError>;
+
+    fn get_categories(&self, team_id: Uuid) -> Result<Vec<RuleCategory>, DbError>;
+
+    fn create_category(&self, category: &RuleCategory) -> Result<RuleCategory, DbError>;
+
+    fn update_category(
+        &self,
+        team_id: Uuid,
+        category_id: Uuid,
+        category: &UpdateCategory,
+    ) -> Result<RuleCategory, DbError>;
+
+    /// Fails while rules, even archived ones, or versions of rules kept for the
+    /// sanctions are still in the category.
+    fn delete_category(&self, team_id: Uuid, category_id: Uuid) -> Result<RuleCategory, DbError>;
+
+    /// Removes the team along with everything which belongs to it.
+    fn delete_team(&self, id: Uuid) -> Result<Team, DbError>;
+
+    fn schedule_team_deletion(&self, id: Uuid, purge_at: NaiveDateTime) -> Result<Team, DbError>;
+
+    fn restore_team(&self, id: Uuid) -> Result<Team, DbError>;
+
+    /// Deletes the teams whose grace period is over.
+    fn purge_teams(&self) -> Result<Vec<Team>, DbError>;
 }
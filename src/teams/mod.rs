@@ -2,3 +2,4 @@ pub mod interface;
 pub mod models;
 pub mod postgres;
 pub mod routes;
+pub mod utils;
@@ -1,31 +1,18 @@
-use diesel::{Insertable, Queryable};
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Datelike,
+};
+use diesel::{sql_types::Varchar, Insertable, Queryable};
 use diesel_as_jsonb::*;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
-use crate::database::schema::teams;
-
-#[derive(Deserialize)]
-pub struct LoginRequest {
-    pub name: String,
-    pub admin_password: Option<String>,
-}
-
-#[derive(Serialize, Debug)]
-pub struct LoginResponse {
-    pub id: Uuid,
-    pub admin_password: Option<String>,
-}
-
-impl From<(LoginRequest, Uuid)> for LoginResponse {
-    fn from((login_request, team_id): (LoginRequest, Uuid)) -> LoginResponse {
-        LoginResponse {
-            id: team_id,
-            admin_password: login_request.admin_password,
-        }
-    }
-}
+use crate::database::schema::{rule_categories, rule_versions, rules, teams};
+use crate::payments::models::Payment;
+use crate::sanctions::models::Sanction;
+use crate::users::models::User;
+use crate::varchar_enum;
 
 #[derive(Deserialize)]
 pub struct UpdateTeamRequest {
@@ -33,6 +20,8 @@ pub struct UpdateTeamRequest {
     pub name: String,
     pub admin_password: String,
     pub rules: Vec<UpdateRuleRequest>,
+    pub currency: Option<String>,
+    pub locale: Option<String>,
 }
 
 impl From<UpdateTeamRequest> for Team {
@@ -46,6 +35,13 @@ impl From<UpdateTeamRequest> for Team {
                 .into_iter()
                 .map(|update_rule_request| update_rule_request.into())
                 .collect(),
+            currency: update_request
+                .currency
+                .unwrap_or_else(|| String::from(DEFAULT_CURRENCY)),
+            locale: update_request
+                .locale
+                .unwrap_or_else(|| String::from(DEFAULT_LOCALE)),
+            purge_at: None,
         }
     }
 }
@@ -60,40 +56,179 @@ impl From<UpdateTeamRequest> for UpdateTeam {
                 .into_iter()
                 .map(|update_rule_request| update_rule_request.into())
                 .collect(),
+            currency: update_request.currency,
+            locale: update_request.locale,
         }
     }
 }
 
-#[derive(Debug, Queryable, Insertable, Serialize, PartialEq, Clone, Default)]
-#[table_name = "teams"]
+pub const DEFAULT_CURRENCY: &str = "EUR";
+pub const DEFAULT_LOCALE: &str = "fr-FR";
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
 pub struct Team {
     pub id: Uuid,
     pub name: String,
+    #[serde(skip_serializing)]
     pub admin_password: String,
+    /// Includes the archived rules, so that older sanctions can resolve them.
     pub rules: Vec<Rule>,
+    /// An ISO 4217 code, in which every price of the team is expressed.
+    pub currency: String,
+    pub locale: String,
+    /// Set when the deletion of the team is pending, until which it can still
+    /// be restored.
+    pub purge_at: Option<NaiveDateTime>,
 }
 
-impl Team {
-    pub fn get_rule(self, rule_id: Uuid) -> Option<Rule> {
-        self.rules.into_iter().find(|rule| rule.id == rule_id)
+impl Default for Team {
+    fn default() -> Team {
+        Team {
+            id: Default::default(),
+            name: Default::default(),
+            admin_password: Default::default(),
+            rules: Default::default(),
+            currency: String::from(DEFAULT_CURRENCY),
+            locale: String::from(DEFAULT_LOCALE),
+            purge_at: None,
+        }
     }
 }
 
-#[derive(AsChangeset, Default)]
+/// The row of a team, whose rules are stored in their own table.
+#[derive(Queryable, Insertable)]
 #[table_name = "teams"]
+pub struct TeamRow {
+    pub id: Uuid,
+    pub name: String,
+    pub admin_password: String,
+    pub currency: String,
+    pub locale: String,
+    pub purge_at: Option<NaiveDateTime>,
+}
+
+impl From<&Team> for TeamRow {
+    fn from(team: &Team) -> TeamRow {
+        TeamRow {
+            id: team.id,
+            name: team.name.clone(),
+            admin_password: team.admin_password.clone(),
+            currency: team.currency.clone(),
+            locale: team.locale.clone(),
+            purge_at: team.purge_at,
+        }
+    }
+}
+
+impl From<(TeamRow, Vec<Rule>)> for Team {
+    fn from((row, rules): (TeamRow, Vec<Rule>)) -> Team {
+        Team {
+            id: row.id,
+            name: row.name,
+            admin_password: row.admin_password,
+            rules,
+            currency: row.currency,
+            locale: row.locale,
+            purge_at: row.purge_at,
+        }
+    }
+}
+
+impl Team {
+    pub fn get_rule(&self, rule_id: Uuid) -> Option<Rule> {
+        self.rules.iter().find(|rule| rule.id == rule_id).cloned()
+    }
+
+    pub fn without_archived_rules(self) -> Team {
+        Team {
+            rules: self
+                .rules
+                .into_iter()
+                .filter(|rule| !rule.is_archived())
+                .collect(),
+            ..self
+        }
+    }
+}
+
+/// The admin password and the name of the team must both be given again, and
+/// without a grace period the team is deleted right away.
+#[derive(Deserialize)]
+pub struct DeleteTeamRequest {
+    pub admin_password: String,
+    pub confirmation: String,
+    pub grace_period_days: Option<i64>,
+}
+
+pub const MAX_GRACE_PERIOD_DAYS: i64 = 90;
+
+/// Everything stored about a team, returned before it is deleted.
+#[derive(Serialize, Debug)]
+pub struct TeamExport {
+    pub team: Team,
+    pub categories: Vec<RuleCategory>,
+    pub users: Vec<User>,
+    pub sanctions: Vec<Sanction>,
+    pub payments: Vec<Payment>,
+    pub exported_at: NaiveDateTime,
+}
+
+#[derive(Default)]
 pub struct UpdateTeam {
     pub name: String,
     pub admin_password: String,
     pub rules: Vec<Rule>,
+    pub currency: Option<String>,
+    pub locale: Option<String>,
+}
+
+/// `None` fields are left unchanged.
+#[derive(AsChangeset)]
+#[table_name = "teams"]
+pub struct UpdateTeamRow {
+    pub name: String,
+    pub admin_password: String,
+    pub currency: Option<String>,
+    pub locale: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateRuleRequest {
     pub id: Option<Uuid>,
     pub name: String,
-    pub category: RuleCategory,
+    #[serde(default)]
+    pub category_id: Option<Uuid>,
+    /// Still sent by older clients, instead of `category_id`.
+    #[serde(default)]
+    pub category: Option<LegacyCategory>,
     pub description: String,
     pub kind: RuleKind,
+    #[serde(default)]
+    pub limits: PriceLimits,
+}
+
+/// The fixed categories of the rules before each team defined its own.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow, Default,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum LegacyCategory {
+    GameDay,
+    #[default]
+    TrainingDay,
+}
+
+varchar_enum!(LegacyCategory);
+
+impl LegacyCategory {
+    /// The name of the default category which replaced it.
+    pub fn category_name(self) -> &'static str {
+        match self {
+            LegacyCategory::GameDay => DEFAULT_CATEGORIES[0].0,
+            LegacyCategory::TrainingDay => DEFAULT_CATEGORIES[1].0,
+        }
+    }
 }
 
 impl From<UpdateRuleRequest> for Rule {
@@ -101,50 +236,231 @@ impl From<UpdateRuleRequest> for Rule {
         Rule {
             id: update_request.id.unwrap_or_else(Uuid::new_v4),
             name: update_request.name,
-            category: update_request.category,
+            category_id: update_request.category_id,
             description: update_request.description,
             kind: update_request.kind,
+            limits: update_request.limits,
+            archived_at: None,
+            category: Default::default(),
         }
     }
 }
 
-#[derive(AsJsonb, Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+/// Selected from the `rules` table through `RULE_COLUMNS`.
+#[derive(Queryable, Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
 pub struct Rule {
     pub id: Uuid,
     pub name: String,
-    pub category: RuleCategory,
+    /// One of the categories of the team, if any.
+    #[serde(default)]
+    pub category_id: Option<Uuid>,
     pub description: String,
     pub kind: RuleKind,
+    #[serde(default)]
+    pub limits: PriceLimits,
+    /// Archived rules are hidden and can't be used for new sanctions, but the
+    /// sanctions which refer to them keep them.
+    #[serde(default)]
+    pub archived_at: Option<NaiveDateTime>,
+    /// Derived from the category for the clients which still read it, until
+    /// the next release.
+    #[serde(skip_deserializing)]
+    pub category: LegacyCategory,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum RuleCategory {
-    GameDay,
-    TrainingDay,
+impl Rule {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+}
+
+/// The row of a rule, ordered within its team by `position`.
+#[derive(Insertable, AsChangeset)]
+#[table_name = "rules"]
+pub struct NewRule {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub name: String,
+    pub category_id: Option<Uuid>,
+    pub description: String,
+    pub kind: RuleKind,
+    pub limits: PriceLimits,
+    pub position: i32,
 }
 
-impl Default for RuleCategory {
-    fn default() -> RuleCategory {
-        RuleCategory::TrainingDay
+impl From<(&Rule, Uuid, i32)> for NewRule {
+    fn from((rule, team_id, position): (&Rule, Uuid, i32)) -> NewRule {
+        NewRule {
+            id: rule.id,
+            team_id,
+            name: rule.name.clone(),
+            category_id: rule.category_id,
+            description: rule.description.clone(),
+            kind: rule.kind.clone(),
+            limits: rule.limits.clone(),
+            position,
+        }
+    }
+}
+
+/// An immutable snapshot of a rule, taken each time the rule is created or
+/// changed, so that sanctions keep the terms under which they were issued.
+#[derive(Queryable, Debug, Serialize, PartialEq, Clone)]
+pub struct RuleVersion {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub team_id: Uuid,
+    pub version: i32,
+    pub name: String,
+    pub description: String,
+    pub kind: RuleKind,
+    pub limits: PriceLimits,
+    pub created_at: NaiveDateTime,
+    pub category_id: Option<Uuid>,
+}
+
+impl RuleVersion {
+    pub fn has_terms_of(&self, rule: &Rule) -> bool {
+        self.name == rule.name
+            && self.category_id == rule.category_id
+            && self.description == rule.description
+            && self.kind == rule.kind
+            && self.limits == rule.limits
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "rule_versions"]
+pub struct NewRuleVersion {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub team_id: Uuid,
+    pub version: i32,
+    pub name: String,
+    pub category_id: Option<Uuid>,
+    pub description: String,
+    pub kind: RuleKind,
+    pub limits: PriceLimits,
+}
+
+impl From<(&Rule, Uuid, i32)> for NewRuleVersion {
+    fn from((rule, team_id, version): (&Rule, Uuid, i32)) -> NewRuleVersion {
+        NewRuleVersion {
+            id: Uuid::new_v4(),
+            rule_id: rule.id,
+            team_id,
+            version,
+            name: rule.name.clone(),
+            category_id: rule.category_id,
+            description: rule.description.clone(),
+            kind: rule.kind.clone(),
+            limits: rule.limits.clone(),
+        }
+    }
+}
+
+#[derive(AsJsonb, Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct PriceLimits {
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    /// Caps the total price of the sanctions of a user for this rule within a
+    /// period.
+    pub period_cap: Option<PeriodCap>,
+    /// Raises the first sanctions of a user for this rule within a period
+    /// until their total reaches the minimum.
+    pub period_floor: Option<PeriodFloor>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct PeriodCap {
+    pub max_price: i64,
+    pub period: ResetPeriod,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct PeriodFloor {
+    pub min_price: i64,
+    pub period: ResetPeriod,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCategoryRequest {
+    pub id: Option<Uuid>,
+    pub name: String,
+    /// A hexadecimal colour such as `#1E88E5`.
+    pub color: String,
+    pub position: i32,
+}
+
+impl From<(UpdateCategoryRequest, Uuid)> for RuleCategory {
+    fn from((update_request, team_id): (UpdateCategoryRequest, Uuid)) -> RuleCategory {
+        RuleCategory {
+            id: update_request.id.unwrap_or_else(Uuid::new_v4),
+            team_id,
+            name: update_request.name,
+            color: update_request.color,
+            position: update_request.position,
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+impl From<UpdateCategoryRequest> for UpdateCategory {
+    fn from(update_request: UpdateCategoryRequest) -> UpdateCategory {
+        UpdateCategory {
+            name: update_request.name,
+            color: update_request.color,
+            position: update_request.position,
+        }
+    }
+}
+
+/// Defined by each team to group its rules, in the order of `position`.
+#[derive(Debug, Clone, Queryable, Insertable, PartialEq, Serialize, Default)]
+#[table_name = "rule_categories"]
+pub struct RuleCategory {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub position: i32,
+}
+
+/// The categories every new team starts with, by name and colour.
+pub const DEFAULT_CATEGORIES: [(&str, &str); 2] =
+    [("Game day", "#1E88E5"), ("Training day", "#43A047")];
+
+#[derive(AsChangeset)]
+#[table_name = "rule_categories"]
+pub struct UpdateCategory {
+    pub name: String,
+    pub color: String,
+    pub position: i32,
+}
+
+/// All prices are integer amounts of cents.
+#[derive(AsJsonb, Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "type")]
 pub enum RuleKind {
     Basic {
-        price: f32,
+        price: i64,
     },
     Multiplication {
-        price_to_multiply: f32,
+        price_to_multiply: i64,
     },
     TimeMultiplication {
-        price_per_time_unit: f32,
+        price_per_time_unit: i64,
         time_unit: TimeUnit,
+        #[serde(default)]
+        rounding: Rounding,
     },
     Monthly {
-        price: f32,
+        price: i64,
+    },
+    /// The n-th sanction of a user for this rule within the reset period costs
+    /// the n-th tier, and the last tier applies beyond.
+    Escalating {
+        tiers: Vec<i64>,
+        reset_period: ResetPeriod,
     },
 }
 
@@ -163,11 +479,12 @@ impl fmt::Display for RuleKind {
             RuleKind::Multiplication { .. } => write!(f, "MULTIPLICATION"),
             RuleKind::TimeMultiplication { .. } => write!(f, "TIME_MULTIPLICATION"),
             RuleKind::Monthly { .. } => write!(f, "MONTHLY"),
+            RuleKind::Escalating { .. } => write!(f, "ESCALATING"),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TimeUnit {
     Second,
@@ -178,3 +495,63 @@ pub enum TimeUnit {
     Month,
     Year,
 }
+
+impl TimeUnit {
+    /// Months and years use their average length in the gregorian calendar.
+    pub fn seconds(self) -> f64 {
+        match self {
+            TimeUnit::Second => 1.0,
+            TimeUnit::Minute => 60.0,
+            TimeUnit::Hour => 3_600.0,
+            TimeUnit::Day => 86_400.0,
+            TimeUnit::Week => 604_800.0,
+            TimeUnit::Month => 2_629_746.0,
+            TimeUnit::Year => 31_556_952.0,
+        }
+    }
+
+    pub fn count(self, seconds: u64, rounding: Rounding) -> i64 {
+        rounding.round(seconds as f64 / self.seconds()) as i64
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResetPeriod {
+    Month,
+    /// Seasons start on September 1st.
+    Season,
+    Never,
+}
+
+impl ResetPeriod {
+    pub fn start(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            ResetPeriod::Month => Some(NaiveDate::from_ymd(date.year(), date.month(), 1)),
+            ResetPeriod::Season => match date.month() {
+                9..=12 => Some(NaiveDate::from_ymd(date.year(), 9, 1)),
+                _ => Some(NaiveDate::from_ymd(date.year() - 1, 9, 1)),
+            },
+            ResetPeriod::Never => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Rounding {
+    #[default]
+    Ceil,
+    Floor,
+    Nearest,
+}
+
+impl Rounding {
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Ceil => value.ceil(),
+            Rounding::Floor => value.floor(),
+            Rounding::Nearest => value.round(),
+        }
+    }
+}
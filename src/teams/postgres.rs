@@ -1,59 +1,608 @@
-use diesel::prelude::*;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    prelude::*,
+    sql_types::{self, Nullable, Varchar},
+};
 use std::ops::Deref;
 use uuid::Uuid;
 
 use super::{
     interface::TeamsDb,
-    models::{Team, UpdateTeam},
+    models::{
+        NewRule, NewRuleVersion, Rule, RuleCategory, RuleVersion, Team, TeamRow, UpdateCategory,
+        UpdateTeam, UpdateTeamRow, DEFAULT_CATEGORIES,
+    },
+    utils::password::{hash_password, verify_password, PasswordMatch},
 };
 use crate::database::{
     postgres::{DbConnection, DbError},
-    schema::teams,
+    schema::{rule_categories, rule_versions, rules, sanctions, teams},
 };
 
+sql_function! {
+    /// The legacy category of the rules of a category, defined along with
+    /// `rule_categories`.
+    fn legacy_category(category_id: Nullable<sql_types::Uuid>) -> Varchar;
+}
+
+type RuleColumns = (
+    rules::id,
+    rules::name,
+    rules::category_id,
+    rules::description,
+    rules::kind,
+    rules::limits,
+    rules::archived_at,
+    legacy_category::HelperType<rules::category_id>,
+);
+
+/// The columns of `rules` selected into a `Rule`.
+const RULE_COLUMNS: RuleColumns = (
+    rules::id,
+    rules::name,
+    rules::category_id,
+    rules::description,
+    rules::kind,
+    rules::limits,
+    rules::archived_at,
+    legacy_category::legacy_category {
+        category_id: rules::category_id,
+    },
+);
+
+fn get_rules(conn: &DbConnection, team_id: Uuid) -> Result<Vec<Rule>, DbError> {
+    let rules: Vec<Rule> = rules::table
+        .filter(rules::team_id.eq(team_id))
+        .order(rules::position)
+        .select(RULE_COLUMNS)
+        .get_results(conn.deref())?;
+
+    Ok(rules)
+}
+
+fn with_rules(conn: &DbConnection, row: TeamRow) -> Result<Team, DbError> {
+    let rules = get_rules(conn, row.id)?;
+
+    Ok((row, rules).into())
+}
+
+/// Adds a version of the rule, unless its latest one already has its terms.
+fn version_rule(conn: &DbConnection, team_id: Uuid, rule: &Rule) -> Result<(), DbError> {
+    let latest: Option<RuleVersion> = rule_versions::table
+        .filter(rule_versions::rule_id.eq(rule.id))
+        .order(rule_versions::version.desc())
+        .first(conn.deref())
+        .optional()?;
+
+    let version = match latest {
+        Some(ref latest) if latest.has_terms_of(rule) => return Ok(()),
+        Some(latest) => latest.version + 1,
+        None => 1,
+    };
+
+    diesel::insert_into(rule_versions::table)
+        .values(&NewRuleVersion::from((rule, team_id, version)))
+        .execute(conn.deref())?;
+
+    Ok(())
+}
+
+/// Refuses to delete rules which sanctions still refer to, as they can only be
+/// archived.
+fn check_unreferenced(conn: &DbConnection, rules: &[Rule]) -> Result<(), DbError> {
+    let rule_ids: Vec<Uuid> = rules.iter().map(|rule| rule.id).collect();
+
+    let referenced: Option<Option<Uuid>> = sanctions::table
+        .filter(sanctions::rule_id.eq_any(&rule_ids))
+        .select(sanctions::rule_id)
+        .first(conn.deref())
+        .optional()?;
+
+    match rules.iter().find(|rule| Some(Some(rule.id)) == referenced) {
+        Some(rule) => Err(DbError::ForeignKeyViolation(format!(
+            "The rule {} is still referred to by sanctions and can only be archived",
+            rule.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Names are unique within a team, which the constraint would only tell in less
+/// clear words.
+fn check_category_name(
+    conn: &DbConnection,
+    team_id: Uuid,
+    category_id: Uuid,
+    name: &str,
+) -> Result<(), DbError> {
+    let existing: Option<Uuid> = rule_categories::table
+        .filter(
+            rule_categories::team_id
+                .eq(team_id)
+                .and(rule_categories::name.eq(name))
+                .and(rule_categories::id.ne(category_id)),
+        )
+        .select(rule_categories::id)
+        .first(conn.deref())
+        .optional()?;
+
+    match existing {
+        Some(_) => Err(DbError::UniqueViolation(format!(
+            "The name {} is already used by another category",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The prices and amounts are stored in the minor unit of the currency, so
+/// changing it would change what every sanction and payment is worth. Payments
+/// always belong to a sanction, so checking the sanctions covers both.
+fn check_currency(conn: &DbConnection, id: Uuid, currency: &Option<String>) -> Result<(), DbError> {
+    let currency = match currency {
+        Some(currency) => currency,
+        None => return Ok(()),
+    };
+
+    let current: String = teams::table
+        .find(id)
+        .select(teams::currency)
+        .for_update()
+        .get_result(conn.deref())?;
+
+    if &current == currency {
+        return Ok(());
+    }
+
+    let sanction: Option<Uuid> = sanctions::table
+        .filter(sanctions::team_id.eq(id))
+        .select(sanctions::id)
+        .first(conn.deref())
+        .optional()?;
+
+    match sanction {
+        Some(_) => Err(DbError::ForeignKeyViolation(format!(
+            "The currency can't be changed from {} to {} once the team has sanctions",
+            current, currency
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Removes the versions of the removed rules, except the ones which sanctions
+/// were priced under.
+fn delete_unreferenced_versions(conn: &DbConnection, rule_ids: &[Uuid]) -> Result<(), DbError> {
+    let referenced = sanctions::table
+        .filter(sanctions::rule_version_id.is_not_null())
+        .select(sanctions::rule_version_id);
+
+    diesel::delete(
+        rule_versions::table.filter(
+            rule_versions::rule_id
+                .eq_any(rule_ids)
+                .and(rule_versions::id.nullable().ne_all(referenced)),
+        ),
+    )
+    .execute(conn.deref())?;
+
+    Ok(())
+}
+
+/// Replaces the active rules of the team, updating in place the rows of the
+/// rules which are kept so that the sanctions still refer to them. The
+/// archived rules are kept even when they aren't given.
+fn replace_rules(conn: &DbConnection, team_id: Uuid, rules: &[Rule]) -> Result<(), DbError> {
+    let rule_ids: Vec<Uuid> = rules.iter().map(|rule| rule.id).collect();
+
+    let removed_rules: Vec<Rule> = rules::table
+        .filter(
+            rules::team_id
+                .eq(team_id)
+                .and(rules::id.ne_all(&rule_ids))
+                .and(rules::archived_at.is_null()),
+        )
+        .select(RULE_COLUMNS)
+        .get_results(conn.deref())?;
+
+    check_unreferenced(conn, &removed_rules)?;
+
+    let removed_ids: Vec<Uuid> = removed_rules.iter().map(|rule| rule.id).collect();
+
+    diesel::delete(rules::table.filter(rules::id.eq_any(&removed_ids))).execute(conn.deref())?;
+    delete_unreferenced_versions(conn, &removed_ids)?;
+
+    for (position, rule) in rules.iter().enumerate() {
+        let new_rule = NewRule::from((rule, team_id, position as i32));
+        let updated = diesel::update(
+            rules::table.filter(rules::team_id.eq(team_id).and(rules::id.eq(rule.id))),
+        )
+        .set(&new_rule)
+        .execute(conn.deref())?;
+
+        if updated == 0 {
+            diesel::insert_into(rules::table)
+                .values(&new_rule)
+                .execute(conn.deref())?;
+        }
+
+        version_rule(conn, team_id, rule)?;
+    }
+
+    Ok(())
+}
+
 impl TeamsDb for DbConnection {
     fn login(&self, name: &str, admin_password: &Option<String>) -> Result<Uuid, DbError> {
-        let team: Team = match admin_password {
-            Some(password) => teams::table
-                .filter(teams::name.eq(name).and(teams::admin_password.eq(password)))
-                .get_result(self.deref())?,
-            None => teams::table
-                .filter(teams::name.eq(name))
-                .get_result(self.deref())?,
-        };
+        let team: TeamRow = teams::table
+            .filter(teams::name.eq(name))
+            .get_result(self.deref())?;
+
+        if team.purge_at.is_some() && admin_password.is_none() {
+            return Err(DbError::NotFound);
+        }
+
+        if let Some(password) = admin_password {
+            match verify_password(password, &team.admin_password)? {
+                PasswordMatch::Hashed => {}
+                PasswordMatch::Plaintext => {
+                    diesel::update(teams::table.find(team.id))
+                        .set(teams::admin_password.eq(hash_password(password)?))
+                        .execute(self.deref())?;
+                }
+                PasswordMatch::Mismatch => return Err(DbError::NotFound),
+            }
+        }
 
         Ok(team.id)
     }
 
+    fn get_team_ids(&self) -> Result<Vec<Uuid>, DbError> {
+        let team_ids: Vec<Uuid> = teams::table
+            .filter(teams::purge_at.is_null())
+            .select(teams::id)
+            .get_results(self.deref())?;
+
+        Ok(team_ids)
+    }
+
     fn get_team(&self, id: Uuid) -> Result<Team, DbError> {
-        let team: Team = teams::table.find(id).get_result(self.deref())?;
+        let row: TeamRow = teams::table.find(id).get_result(self.deref())?;
 
-        Ok(team)
+        with_rules(self, row)
     }
 
     fn create_team(&self, team: &Team) -> Result<Team, DbError> {
-        let team: Team = diesel::insert_into(teams::table)
-            .values(team)
-            .get_result(self.deref())?;
+        let row = TeamRow {
+            admin_password: hash_password(&team.admin_password)?,
+            ..team.into()
+        };
+
+        self.deref().transaction(|| {
+            let row: TeamRow = diesel::insert_into(teams::table)
+                .values(&row)
+                .get_result(self.deref())?;
+
+            let categories: Vec<RuleCategory> = DEFAULT_CATEGORIES
+                .iter()
+                .enumerate()
+                .map(|(position, (name, color))| RuleCategory {
+                    id: Uuid::new_v4(),
+                    team_id: row.id,
+                    name: String::from(*name),
+                    color: String::from(*color),
+                    position: position as i32,
+                })
+                .collect();
 
-        Ok(team)
+            diesel::insert_into(rule_categories::table)
+                .values(&categories)
+                .execute(self.deref())?;
+
+            replace_rules(self, row.id, &team.rules)?;
+
+            with_rules(self, row)
+        })
     }
 
     fn update_team(&self, id: Uuid, team: &UpdateTeam) -> Result<Team, DbError> {
-        let team: Team = diesel::update(teams::table.find(id))
-            .set(team)
+        let row = UpdateTeamRow {
+            name: team.name.clone(),
+            admin_password: hash_password(&team.admin_password)?,
+            currency: team.currency.clone(),
+            locale: team.locale.clone(),
+        };
+
+        self.deref().transaction(|| {
+            check_currency(self, id, &team.currency)?;
+
+            let row: TeamRow = diesel::update(teams::table.find(id))
+                .set(&row)
+                .get_result(self.deref())?;
+
+            replace_rules(self, id, &team.rules)?;
+
+            with_rules(self, row)
+        })
+    }
+
+    fn create_rule(&self, team_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+        self.deref().transaction(|| {
+            // Locking the team serializes the positions given to its new rules.
+            teams::table
+                .find(team_id)
+                .select(teams::id)
+                .for_update()
+                .get_result::<Uuid>(self.deref())?;
+
+            let existing: Option<Uuid> = rules::table
+                .find(rule.id)
+                .select(rules::id)
+                .get_result(self.deref())
+                .optional()?;
+
+            if existing.is_some() {
+                return Err(DbError::UniqueViolation(String::from(
+                    "The field id is already used by another rule",
+                )));
+            }
+
+            let last_position: Option<i32> = rules::table
+                .filter(rules::team_id.eq(team_id))
+                .select(diesel::dsl::max(rules::position))
+                .get_result(self.deref())?;
+            let position = last_position.map_or(0, |position| position + 1);
+
+            let rule: Rule = diesel::insert_into(rules::table)
+                .values(&NewRule::from((rule, team_id, position)))
+                .returning(RULE_COLUMNS)
+                .get_result(self.deref())?;
+
+            version_rule(self, team_id, &rule)?;
+
+            Ok(rule)
+        })
+    }
+
+    fn update_rule(&self, team_id: Uuid, rule_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+        self.deref().transaction(|| {
+            let rule: Rule = diesel::update(
+                rules::table.filter(rules::team_id.eq(team_id).and(rules::id.eq(rule_id))),
+            )
+            .set((
+                rules::name.eq(&rule.name),
+                rules::category_id.eq(rule.category_id),
+                rules::description.eq(&rule.description),
+                rules::kind.eq(&rule.kind),
+                rules::limits.eq(&rule.limits),
+            ))
+            .returning(RULE_COLUMNS)
             .get_result(self.deref())?;
 
-        Ok(team)
+            version_rule(self, team_id, &rule)?;
+
+            Ok(rule)
+        })
+    }
+
+    fn delete_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        self.deref().transaction(|| {
+            let rule: Rule = rules::table
+                .filter(rules::team_id.eq(team_id).and(rules::id.eq(rule_id)))
+                .select(RULE_COLUMNS)
+                .for_update()
+                .get_result(self.deref())?;
+
+            check_unreferenced(self, &[rule])?;
+
+            let rule: Rule = diesel::delete(rules::table.find(rule_id))
+                .returning(RULE_COLUMNS)
+                .get_result(self.deref())?;
+            delete_unreferenced_versions(self, &[rule_id])?;
+
+            Ok(rule)
+        })
+    }
+
+    fn archive_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        let rule: Rule = diesel::update(
+            rules::table.filter(
+                rules::team_id
+                    .eq(team_id)
+                    .and(rules::id.eq(rule_id))
+                    .and(rules::archived_at.is_null()),
+            ),
+        )
+        .set(rules::archived_at.eq(Utc::now().naive_utc()))
+        .returning(RULE_COLUMNS)
+        .get_result(self.deref())?;
+
+        Ok(rule)
+    }
+
+    fn restore_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        let rule: Rule = diesel::update(
+            rules::table.filter(
+                rules::team_id
+                    .eq(team_id)
+                    .and(rules::id.eq(rule_id))
+                    .and(rules::archived_at.is_not_null()),
+            ),
+        )
+        .set(rules::archived_at.eq(None::<NaiveDateTime>))
+        .returning(RULE_COLUMNS)
+        .get_result(self.deref())?;
+
+        Ok(rule)
+    }
+
+    fn get_rule_versions(&self, team_id: Uuid, rule_id: Uuid) -> Result<Vec<RuleVersion>, DbError> {
+        let versions: Vec<RuleVersion> = rule_versions::table
+            .filter(
+                rule_versions::team_id
+                    .eq(team_id)
+                    .and(rule_versions::rule_id.eq(rule_id)),
+            )
+            .order(rule_versions::version)
+            .get_results(self.deref())?;
+
+        Ok(versions)
+    }
+
+    fn get_rule_version(&self, team_id: Uuid, version_id: Uuid) -> Result<RuleVersion, DbError> {
+        let version: RuleVersion = rule_versions::table
+            .filter(
+                rule_versions::team_id
+                    .eq(team_id)
+                    .and(rule_versions::id.eq(version_id)),
+            )
+            .get_result(self.deref())?;
+
+        Ok(version)
+    }
+
+    fn get_categories(&self, team_id: Uuid) -> Result<Vec<RuleCategory>, DbError> {
+        let categories: Vec<RuleCategory> = rule_categories::table
+            .filter(rule_categories::team_id.eq(team_id))
+            .order((rule_categories::position, rule_categories::name))
+            .get_results(self.deref())?;
+
+        Ok(categories)
+    }
+
+    fn create_category(&self, category: &RuleCategory) -> Result<RuleCategory, DbError> {
+        check_category_name(self, category.team_id, category.id, &category.name)?;
+
+        let category: RuleCategory = diesel::insert_into(rule_categories::table)
+            .values(category)
+            .get_result(self.deref())?;
+
+        Ok(category)
+    }
+
+    fn update_category(
+        &self,
+        team_id: Uuid,
+        category_id: Uuid,
+        category: &UpdateCategory,
+    ) -> Result<RuleCategory, DbError> {
+        check_category_name(self, team_id, category_id, &category.name)?;
+
+        let category: RuleCategory = diesel::update(
+            rule_categories::table.filter(
+                rule_categories::team_id
+                    .eq(team_id)
+                    .and(rule_categories::id.eq(category_id)),
+            ),
+        )
+        .set(category)
+        .get_result(self.deref())?;
+
+        Ok(category)
+    }
+
+    fn delete_category(&self, team_id: Uuid, category_id: Uuid) -> Result<RuleCategory, DbError> {
+        self.deref().transaction(|| {
+            let category: RuleCategory = rule_categories::table
+                .filter(
+                    rule_categories::team_id
+                        .eq(team_id)
+                        .and(rule_categories::id.eq(category_id)),
+                )
+                .for_update()
+                .get_result(self.deref())?;
+
+            let rule_name: Option<String> = rules::table
+                .filter(rules::category_id.eq(category_id))
+                .select(rules::name)
+                .first(self.deref())
+                .optional()?;
+
+            if let Some(rule_name) = rule_name {
+                return Err(DbError::ForeignKeyViolation(format!(
+                    "The category {} still has the rule {} and can't be deleted",
+                    category.name, rule_name
+                )));
+            }
+
+            let version_name: Option<String> = rule_versions::table
+                .filter(rule_versions::category_id.eq(category_id))
+                .select(rule_versions::name)
+                .first(self.deref())
+                .optional()?;
+
+            if let Some(version_name) = version_name {
+                return Err(DbError::ForeignKeyViolation(format!(
+                    "The category {} is still used by a version of the rule {} and can't be deleted",
+                    category.name, version_name
+                )));
+            }
+
+            let category: RuleCategory = diesel::delete(rule_categories::table.find(category_id))
+                .get_result(self.deref())?;
+
+            Ok(category)
+        })
+    }
+
+    fn delete_team(&self, id: Uuid) -> Result<Team, DbError> {
+        self.deref().transaction(|| {
+            let rules = get_rules(self, id)?;
+            let row: TeamRow = diesel::delete(teams::table.find(id)).get_result(self.deref())?;
+
+            Ok((row, rules).into())
+        })
+    }
+
+    fn schedule_team_deletion(&self, id: Uuid, purge_at: NaiveDateTime) -> Result<Team, DbError> {
+        let row: TeamRow = diesel::update(teams::table.find(id))
+            .set(teams::purge_at.eq(purge_at))
+            .get_result(self.deref())?;
+
+        with_rules(self, row)
+    }
+
+    fn restore_team(&self, id: Uuid) -> Result<Team, DbError> {
+        let row: TeamRow =
+            diesel::update(teams::table.find(id).filter(teams::purge_at.is_not_null()))
+                .set(teams::purge_at.eq(None::<NaiveDateTime>))
+                .get_result(self.deref())?;
+
+        with_rules(self, row)
+    }
+
+    fn purge_teams(&self) -> Result<Vec<Team>, DbError> {
+        self.deref().transaction(|| {
+            let rows: Vec<TeamRow> = teams::table
+                .filter(teams::purge_at.le(Utc::now().naive_utc()))
+                .for_update()
+                .get_results(self.deref())?;
+            let team_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+            let teams = rows
+                .into_iter()
+                .map(|row| with_rules(self, row))
+                .collect::<Result<Vec<Team>, DbError>>()?;
+
+            diesel::delete(teams::table.filter(teams::id.eq_any(&team_ids)))
+                .execute(self.deref())?;
+
+            Ok(teams)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, NaiveDate};
     use diesel::result::Error;
 
     use super::*;
+    use crate::sanctions::{
+        interface::SanctionsDb,
+        models::{CreateSanction, SanctionInfo},
+    };
+    use crate::teams::models::LegacyCategory;
     use crate::test_utils::postgres::init_connection;
+    use crate::users::{interface::UsersDb, models::User};
 
     #[test]
     fn test_login() {
@@ -88,8 +637,10 @@ mod tests {
                 })
                 .unwrap();
 
+            assert_ne!(created_team.admin_password, String::from("password"));
+
             let team_id = conn
-                .login(&created_team.name, &Some(created_team.admin_password))
+                .login(&created_team.name, &Some(String::from("password")))
                 .unwrap();
 
             assert_eq!(team_id, created_team.id);
@@ -98,6 +649,34 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_login_rehashes_plaintext_password() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+
+            diesel::update(teams::table.find(team_id))
+                .set(teams::admin_password.eq("password"))
+                .execute(conn.deref())
+                .unwrap();
+
+            let logged_team_id = conn.login("", &Some(String::from("password"))).unwrap();
+
+            assert_eq!(logged_team_id, team_id);
+
+            let team = conn.get_team(team_id).unwrap();
+
+            assert_ne!(team.admin_password, String::from("password"));
+            assert_eq!(
+                conn.login("", &Some(String::from("password"))).unwrap(),
+                team_id
+            );
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_login_fails() {
         let conn = init_connection();
@@ -130,6 +709,21 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_get_team_ids() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let created_team = conn.create_team(&Team::default()).unwrap();
+
+            let team_ids = conn.get_team_ids().unwrap();
+
+            assert!(team_ids.contains(&created_team.id));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_get_team() {
         let conn = init_connection();
@@ -191,6 +785,119 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_update_team_currency() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                id: Uuid::new_v4(),
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let update = |currency: &str| UpdateTeam {
+                rules: vec![rule.clone()],
+                currency: Some(String::from(currency)),
+                ..Default::default()
+            };
+
+            let team = conn.update_team(team_id, &update("USD")).unwrap();
+
+            assert_eq!(team.currency, "USD");
+
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            conn.create_sanctions(&[CreateSanction {
+                team_id,
+                user_id,
+                sanction_info: SanctionInfo {
+                    associated_rule: rule.id,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }])
+            .unwrap();
+
+            assert_eq!(
+                conn.update_team(team_id, &update("JPY")).unwrap_err(),
+                DbError::ForeignKeyViolation(String::from(
+                    "The currency can't be changed from USD to JPY once the team has sanctions"
+                ))
+            );
+            assert!(conn.update_team(team_id, &update("USD")).is_ok());
+            assert_eq!(conn.get_team(team_id).unwrap().currency, "USD");
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_update_team_rules() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                id: Uuid::new_v4(),
+                name: String::from("Retard"),
+                ..Default::default()
+            };
+            let rule_2 = Rule {
+                id: Uuid::new_v4(),
+                name: String::from("Oubli"),
+                ..Default::default()
+            };
+            let id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone(), rule_2.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let rules = vec![
+                Rule {
+                    name: String::from("Oubli de maillot"),
+                    ..rule_2
+                },
+                Rule {
+                    id: Uuid::new_v4(),
+                    name: String::from("Carton rouge"),
+                    ..Default::default()
+                },
+            ];
+
+            let team = conn
+                .update_team(
+                    id,
+                    &UpdateTeam {
+                        rules: rules.clone(),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(team.rules, rules);
+            assert_eq!(conn.get_team(id).unwrap().rules, rules);
+            assert_eq!(
+                conn.delete_rule(id, rule.id).unwrap_err(),
+                DbError::NotFound
+            );
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_update_unexisting_team() {
         let conn = init_connection();
@@ -201,4 +908,446 @@ mod tests {
 
         assert_eq!(error, DbError::NotFound);
     }
+
+    #[test]
+    fn test_delete_team() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            conn.delete_team(team_id).unwrap();
+
+            assert_eq!(conn.get_team(team_id).unwrap_err(), DbError::NotFound);
+            assert_eq!(
+                conn.get_user(team_id, user_id).unwrap_err(),
+                DbError::NotFound
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_schedule_team_deletion() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let created_team = conn
+                .create_team(&Team {
+                    name: String::from("CHBC"),
+                    admin_password: String::from("password"),
+                    ..Default::default()
+                })
+                .unwrap();
+            let purge_at = NaiveDate::from_ymd(2100, 1, 1).and_hms(0, 0, 0);
+
+            let team = conn
+                .schedule_team_deletion(created_team.id, purge_at)
+                .unwrap();
+
+            assert_eq!(team.purge_at, Some(purge_at));
+            assert!(!conn.get_team_ids().unwrap().contains(&team.id));
+            assert_eq!(conn.login("CHBC", &None).unwrap_err(), DbError::NotFound);
+            assert_eq!(
+                conn.login("CHBC", &Some(String::from("password"))).unwrap(),
+                team.id
+            );
+
+            conn.purge_teams().unwrap();
+
+            let team = conn.restore_team(team.id).unwrap();
+
+            assert_eq!(team.purge_at, None);
+            assert_eq!(conn.login("CHBC", &None).unwrap(), team.id);
+            assert_eq!(conn.restore_team(team.id).unwrap_err(), DbError::NotFound);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_purge_teams() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            conn.schedule_team_deletion(team_id, Utc::now().naive_utc() - Duration::days(1))
+                .unwrap();
+
+            let purged_teams = conn.purge_teams().unwrap();
+
+            assert!(purged_teams.iter().any(|team| team.id == team_id));
+            assert_eq!(conn.get_team(team_id).unwrap_err(), DbError::NotFound);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_edit_rules() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                id: Uuid::new_v4(),
+                name: String::from("Late"),
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let new_rule = conn
+                .create_rule(
+                    team_id,
+                    &Rule {
+                        id: Uuid::new_v4(),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                conn.create_rule(team_id, &rule).unwrap_err(),
+                DbError::UniqueViolation(String::from(
+                    "The field id is already used by another rule"
+                ))
+            );
+
+            let updated_rule = conn
+                .update_rule(
+                    team_id,
+                    rule.id,
+                    &Rule {
+                        name: String::from("Absent"),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(updated_rule.id, rule.id);
+            assert_eq!(
+                conn.get_team(team_id).unwrap().rules,
+                vec![updated_rule, new_rule.clone()]
+            );
+
+            conn.delete_rule(team_id, rule.id).unwrap();
+
+            assert_eq!(conn.get_team(team_id).unwrap().rules, vec![new_rule]);
+            assert_eq!(
+                conn.delete_rule(team_id, rule.id).unwrap_err(),
+                DbError::NotFound
+            );
+            assert_eq!(
+                conn.update_rule(Uuid::new_v4(), rule.id, &rule)
+                    .unwrap_err(),
+                DbError::NotFound
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_rule_versions() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                id: Uuid::new_v4(),
+                name: String::from("Late"),
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            conn.update_rule(
+                team_id,
+                rule.id,
+                &Rule {
+                    name: String::from("Absent"),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            conn.update_team(
+                team_id,
+                &UpdateTeam {
+                    rules: conn.get_team(team_id).unwrap().rules,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let versions = conn.get_rule_versions(team_id, rule.id).unwrap();
+
+            assert_eq!(versions.len(), 2);
+            assert_eq!((versions[0].version, &versions[0].name[..]), (1, "Late"));
+            assert_eq!((versions[1].version, &versions[1].name[..]), (2, "Absent"));
+            assert_eq!(
+                conn.get_rule_version(team_id, versions[0].id).unwrap(),
+                versions[0]
+            );
+            assert_eq!(
+                conn.get_rule_version(Uuid::new_v4(), versions[0].id)
+                    .unwrap_err(),
+                DbError::NotFound
+            );
+
+            conn.delete_rule(team_id, rule.id).unwrap();
+
+            assert!(conn.get_rule_versions(team_id, rule.id).unwrap().is_empty());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_archive_rules() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let rule = Rule {
+                id: Uuid::new_v4(),
+                name: String::from("Late"),
+                ..Default::default()
+            };
+            let team_id = conn
+                .create_team(&Team {
+                    rules: vec![rule.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            conn.create_sanctions(&[CreateSanction {
+                team_id,
+                user_id,
+                sanction_info: SanctionInfo {
+                    associated_rule: rule.id,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }])
+            .unwrap();
+
+            let error = DbError::ForeignKeyViolation(String::from(
+                "The rule Late is still referred to by sanctions and can only be archived",
+            ));
+
+            assert_eq!(
+                conn.update_team(team_id, &UpdateTeam::default())
+                    .unwrap_err(),
+                error
+            );
+            assert_eq!(conn.delete_rule(team_id, rule.id).unwrap_err(), error);
+
+            let archived_rule = conn.archive_rule(team_id, rule.id).unwrap();
+
+            assert!(archived_rule.is_archived());
+            assert_eq!(
+                conn.archive_rule(team_id, rule.id).unwrap_err(),
+                DbError::NotFound
+            );
+            assert_eq!(
+                conn.update_team(team_id, &UpdateTeam::default())
+                    .unwrap()
+                    .rules,
+                vec![archived_rule]
+            );
+
+            let restored_rule = conn.restore_rule(team_id, rule.id).unwrap();
+
+            assert_eq!(restored_rule, rule);
+            assert_eq!(
+                conn.restore_rule(team_id, rule.id).unwrap_err(),
+                DbError::NotFound
+            );
+            assert!(conn.delete_team(team_id).is_ok());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_categories() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let other_team_id = conn
+                .create_team(&Team {
+                    id: Uuid::new_v4(),
+                    name: String::from("Other team"),
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let categories = conn.get_categories(team_id).unwrap();
+            let names: Vec<&str> = categories
+                .iter()
+                .map(|category| category.name.as_str())
+                .collect();
+
+            assert_eq!(names, vec!["Game day", "Training day"]);
+
+            let category = conn
+                .create_category(&RuleCategory {
+                    id: Uuid::new_v4(),
+                    team_id,
+                    name: String::from("Away trip"),
+                    color: String::from("#FB8C00"),
+                    position: 2,
+                })
+                .unwrap();
+
+            assert_eq!(
+                conn.create_category(&RuleCategory {
+                    id: Uuid::new_v4(),
+                    name: String::from("Away trip"),
+                    ..category.clone()
+                })
+                .unwrap_err(),
+                DbError::UniqueViolation(String::from(
+                    "The name Away trip is already used by another category"
+                ))
+            );
+            assert_eq!(
+                conn.update_category(
+                    team_id,
+                    category.id,
+                    &UpdateCategory {
+                        name: String::from("Game day"),
+                        color: category.color.clone(),
+                        position: 0,
+                    },
+                )
+                .unwrap_err(),
+                DbError::UniqueViolation(String::from(
+                    "The name Game day is already used by another category"
+                ))
+            );
+
+            let category = conn
+                .update_category(
+                    team_id,
+                    category.id,
+                    &UpdateCategory {
+                        name: String::from("Team dinner"),
+                        color: category.color.clone(),
+                        position: 0,
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(conn.get_categories(team_id).unwrap()[1], category);
+            assert_eq!(
+                conn.create_rule(
+                    other_team_id,
+                    &Rule {
+                        category_id: Some(category.id),
+                        ..Default::default()
+                    },
+                )
+                .unwrap_err(),
+                DbError::ForeignKeyViolation(String::from(
+                    "The key category_id doesn't refer to anything"
+                ))
+            );
+
+            let rule = conn
+                .create_rule(
+                    team_id,
+                    &Rule {
+                        name: String::from("Late for dinner"),
+                        category_id: Some(category.id),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            conn.archive_rule(team_id, rule.id).unwrap();
+
+            assert_eq!(
+                conn.delete_category(team_id, category.id).unwrap_err(),
+                DbError::ForeignKeyViolation(String::from(
+                    "The category Team dinner still has the rule Late for dinner and can't be deleted"
+                ))
+            );
+
+            conn.delete_rule(team_id, rule.id).unwrap();
+
+            let rule = conn
+                .create_rule(
+                    team_id,
+                    &Rule {
+                        name: String::from("Late for dinner"),
+                        category_id: Some(category.id),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(rule.category, LegacyCategory::TrainingDay);
+            assert_eq!(
+                conn.update_rule(
+                    team_id,
+                    rule.id,
+                    &Rule {
+                        category_id: Some(categories[0].id),
+                        ..rule.clone()
+                    },
+                )
+                .unwrap()
+                .category,
+                LegacyCategory::GameDay
+            );
+
+            assert_eq!(
+                conn.delete_category(team_id, category.id).unwrap_err(),
+                DbError::ForeignKeyViolation(String::from(
+                    "The category Team dinner is still used by a version of the rule Late for dinner and can't be deleted"
+                ))
+            );
+
+            conn.delete_rule(team_id, rule.id).unwrap();
+
+            assert_eq!(
+                conn.delete_category(other_team_id, category.id)
+                    .unwrap_err(),
+                DbError::NotFound
+            );
+            assert_eq!(
+                conn.delete_category(team_id, category.id).unwrap(),
+                category
+            );
+            assert_eq!(conn.get_categories(team_id).unwrap().len(), 2);
+
+            Ok(())
+        });
+    }
 }
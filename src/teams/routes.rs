@@ -1,128 +1,439 @@
+use chrono::{Duration, Utc};
 use rouille::{input::json::json_input, router, Request};
 use serde::Serialize;
 use uuid::Uuid;
 
 use super::{
     interface::TeamsDb,
-    models::{LoginRequest, LoginResponse, Team, UpdateTeam, UpdateTeamRequest},
+    models::{
+        DeleteTeamRequest, Rule, RuleCategory, RuleKind, RuleVersion, Team, TeamExport,
+        UpdateCategory, UpdateCategoryRequest, UpdateRuleRequest, UpdateTeam, UpdateTeamRequest,
+        MAX_GRACE_PERIOD_DAYS,
+    },
+    utils::currency::{is_currency, is_locale},
 };
 use crate::api::models::ErrorResponse;
+use crate::audit::interface::AuditDb;
+use crate::database::postgres::DbError;
+use crate::payments::interface::PaymentsDb;
+use crate::sanctions::{
+    interface::SanctionsDb,
+    utils::parameters::{ParameterError, ParameterErrorKind},
+};
+use crate::users::interface::UsersDb;
 
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum ResponseWrapper {
-    Login(LoginResponse),
     Team(Team),
+    Export(TeamExport),
+    Rules(Vec<Rule>),
+    Rule(Rule),
+    RuleVersions(Vec<RuleVersion>),
+    Categories(Vec<RuleCategory>),
+    Category(RuleCategory),
 }
 
-pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResponseWrapper, ErrorResponse>
+fn include_archived(request: &Request) -> Result<bool, ParameterError> {
+    match request.get_param("include_archived") {
+        Some(value) => value.parse::<bool>().map_err(|_| ParameterError {
+            parameter_name: String::from("include_archived"),
+            kind: ParameterErrorKind::UnvalidType {
+                expected_type: String::from("boolean"),
+            },
+        }),
+        None => Ok(false),
+    }
+}
+
+fn validate_currency(update_request: &UpdateTeamRequest) -> Result<(), ErrorResponse> {
+    if let Some(currency) = &update_request.currency {
+        if !is_currency(currency) {
+            return Err(ErrorResponse::not_valid(format!(
+                "{} isn't an ISO 4217 currency code",
+                currency
+            )));
+        }
+    }
+
+    if let Some(locale) = &update_request.locale {
+        if !is_locale(locale) {
+            return Err(ErrorResponse::not_valid(format!(
+                "{} isn't a valid locale",
+                locale
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_rule(update_request: &UpdateRuleRequest) -> Result<(), ErrorResponse> {
+    if let RuleKind::Escalating { tiers, .. } = &update_request.kind {
+        if tiers.is_empty() {
+            return Err(ErrorResponse::not_valid(format!(
+                "The rule {} must have at least one tier",
+                update_request.name
+            )));
+        }
+    }
+
+    let limits = &update_request.limits;
+
+    let kind_prices = match &update_request.kind {
+        RuleKind::Basic { price } | RuleKind::Monthly { price } => vec![*price],
+        RuleKind::Multiplication { price_to_multiply } => vec![*price_to_multiply],
+        RuleKind::TimeMultiplication {
+            price_per_time_unit,
+            ..
+        } => vec![*price_per_time_unit],
+        RuleKind::Escalating { tiers, .. } => tiers.clone(),
+    };
+    let limit_prices = [
+        limits.min_price,
+        limits.max_price,
+        limits.period_cap.map(|period_cap| period_cap.max_price),
+        limits
+            .period_floor
+            .map(|period_floor| period_floor.min_price),
+    ];
+
+    if kind_prices
+        .iter()
+        .chain(limit_prices.iter().flatten())
+        .any(|price| *price < 0)
+    {
+        return Err(ErrorResponse::not_valid(format!(
+            "The prices of the rule {} can't be negative",
+            update_request.name
+        )));
+    }
+
+    if let (Some(min_price), Some(max_price)) = (limits.min_price, limits.max_price) {
+        if min_price > max_price {
+            return Err(ErrorResponse::not_valid(format!(
+                "The minimum price of the rule {} is higher than its maximum price",
+                update_request.name
+            )));
+        }
+    }
+
+    if let (Some(period_floor), Some(period_cap)) = (limits.period_floor, limits.period_cap) {
+        if period_floor.min_price > period_cap.max_price {
+            return Err(ErrorResponse::not_valid(format!(
+                "The period minimum of the rule {} is higher than its period cap",
+                update_request.name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_category(update_request: &UpdateCategoryRequest) -> Result<(), ErrorResponse> {
+    let color = &update_request.color;
+    let is_color = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if !is_color {
+        return Err(ErrorResponse::not_valid(format!(
+            "{} isn't a hexadecimal colour such as #1E88E5",
+            color
+        )));
+    }
+
+    Ok(())
+}
+
+/// Gives the rules sent with a legacy category the default category of the team
+/// with the same name, unless they already have a category.
+fn resolve_categories<T>(
+    db: &T,
+    team_id: Uuid,
+    rules: &mut [UpdateRuleRequest],
+) -> Result<(), DbError>
 where
     T: TeamsDb,
 {
-    router!(request,
-        (POST) (/login) => {
-            let input: LoginRequest = json_input(request)?;
+    if rules.iter().all(|rule| rule.category.is_none()) {
+        return Ok(());
+    }
 
-            let team_id = db.login(&input.name, &input.admin_password)?;
+    let categories = db.get_categories(team_id)?;
 
-            let result: LoginResponse = (input, team_id).into();
+    for rule in rules.iter_mut() {
+        if let (None, Some(legacy)) = (rule.category_id, rule.category) {
+            let category = categories
+                .iter()
+                .find(|category| category.name == legacy.category_name())
+                .ok_or_else(|| {
+                    DbError::ForeignKeyViolation(format!(
+                        "The category {} of the rule {} doesn't exist anymore",
+                        legacy.category_name(),
+                        rule.name
+                    ))
+                })?;
 
-            Ok(ResponseWrapper::Login(result))
-        },
+            rule.category_id = Some(category.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// The default categories only exist once the team is created, so the rules
+/// sent with a legacy category are only added afterwards.
+fn create_team<T>(db: &T, mut input: UpdateTeamRequest) -> Result<Team, DbError>
+where
+    T: TeamsDb + AuditDb,
+{
+    if input.rules.iter().all(|rule| rule.category.is_none()) {
+        return db.create_team(&input.into());
+    }
+
+    let mut rules = std::mem::take(&mut input.rules);
+    let team: Team = input.into();
+
+    db.transaction(|| {
+        let team = db.create_team(&team)?;
+        resolve_categories(db, team.id, &mut rules)?;
+
+        let rules = rules
+            .into_iter()
+            .map(|rule| db.create_rule(team.id, &rule.into()))
+            .collect::<Result<Vec<Rule>, DbError>>()?;
+
+        Ok(Team { rules, ..team })
+    })
+}
+
+fn export_team<T>(db: &T, team: Team) -> Result<TeamExport, DbError>
+where
+    T: TeamsDb + UsersDb + SanctionsDb + PaymentsDb,
+{
+    Ok(TeamExport {
+        categories: db.get_categories(team.id)?,
+        users: db.get_users(team.id, true)?,
+        sanctions: db.get_sanctions(team.id, None, true)?,
+        payments: db.get_payments(team.id, None)?,
+        exported_at: Utc::now().naive_utc(),
+        team,
+    })
+}
+
+/// Deletes the team once its admin password and its name have been given
+/// again, right away or at the end of the grace period, and returns its export.
+fn delete_team<T>(request: &Request, db: &T, id: Uuid) -> Result<ResponseWrapper, ErrorResponse>
+where
+    T: TeamsDb + UsersDb + SanctionsDb + PaymentsDb,
+{
+    let input = json_input::<DeleteTeamRequest>(request)?;
+
+    let purge_at = match input.grace_period_days {
+        None | Some(0) => None,
+        Some(days) if days > 0 && days <= MAX_GRACE_PERIOD_DAYS => {
+            Some(Utc::now().naive_utc() + Duration::days(days))
+        }
+        Some(_) => {
+            return Err(ErrorResponse::not_valid(format!(
+                "The grace period must be between 0 and {} days",
+                MAX_GRACE_PERIOD_DAYS
+            )))
+        }
+    };
+
+    let team = db.get_team(id)?;
+
+    if input.confirmation != team.name {
+        return Err(ErrorResponse::not_valid(String::from(
+            "The confirmation must be the name of the team",
+        )));
+    }
+
+    match db.login(&team.name, &Some(input.admin_password)) {
+        Ok(_) => {}
+        Err(DbError::NotFound) => {
+            return Err(ErrorResponse::unauthorized(String::from(
+                "The admin password is wrong",
+            )))
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    let export = export_team(db, team)?;
+
+    let team = match purge_at {
+        Some(purge_at) => db.schedule_team_deletion(id, purge_at)?,
+        None => db.delete_team(id)?,
+    };
+
+    Ok(ResponseWrapper::Export(TeamExport { team, ..export }))
+}
+
+pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResponseWrapper, ErrorResponse>
+where
+    T: TeamsDb + UsersDb + SanctionsDb + PaymentsDb + AuditDb,
+{
+    router!(request,
         (POST) (/teams) => {
-            let input: Team = json_input::<UpdateTeamRequest>(request)?.into();
+            let input = json_input::<UpdateTeamRequest>(request)?;
+            validate_currency(&input)?;
+            input.rules.iter().try_for_each(validate_rule)?;
 
-            let result: Team = db.create_team(&input)?;
+            let result: Team = create_team(db, input)?;
 
             Ok(ResponseWrapper::Team(result))
         },
         (GET) (/teams/{id:Uuid}) => {
-            let result: Team = db.get_team(id)?;
+            let mut result: Team = db.get_team(id)?;
+
+            if !include_archived(request)? {
+                result = result.without_archived_rules();
+            }
 
             Ok(ResponseWrapper::Team(result))
         },
         (POST) (/teams/{id:Uuid}) => {
-            let input: UpdateTeam = json_input::<UpdateTeamRequest>(request)?.into();
+            let mut input = json_input::<UpdateTeamRequest>(request)?;
+            validate_currency(&input)?;
+            input.rules.iter().try_for_each(validate_rule)?;
+            resolve_categories(db, id, &mut input.rules)?;
+            let input: UpdateTeam = input.into();
 
             let result: Team = db.update_team(id, &input)?;
 
             Ok(ResponseWrapper::Team(result))
         },
-        _ => {
-            Err(ErrorResponse::not_found())
-        }
-    )
-}
+        (DELETE) (/teams/{id:Uuid}) => {
+            delete_team(request, db, id)
+        },
+        (POST) (/teams/{id:Uuid}/restore) => {
+            let result: Team = db.restore_team(id)?;
 
-#[cfg(test)]
-mod tests {
-    use serde_json::json;
+            Ok(ResponseWrapper::Team(result))
+        },
+        (GET) (/teams/{team_id:Uuid}/rules) => {
+            let mut team = db.get_team(team_id)?;
 
-    use super::*;
-    use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
-    use crate::test_utils::routes::{DbMock, TeamsDbMock};
+            if !include_archived(request)? {
+                team = team.without_archived_rules();
+            }
 
-    #[test]
-    fn test_login() {
-        let login_request = json!({
-            "name": "CHBC",
-        });
+            let result = team.rules;
 
-        let response = json!(handle_request(
-            &RequestBuilder::post(String::from("/login"), &login_request),
-            &DbMock::default(),
-        )
-        .unwrap());
+            Ok(ResponseWrapper::Rules(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/rules) => {
+            let mut input = json_input::<UpdateRuleRequest>(request)?;
+            validate_rule(&input)?;
+            resolve_categories(db, team_id, std::slice::from_mut(&mut input))?;
+            let input: Rule = input.into();
 
-        assert_eq!(response["admin_password"], serde_json::Value::Null);
-    }
+            let result = db.create_rule(team_id, &input)?;
 
-    #[test]
-    fn test_login_with_password() {
-        let login_request = json!({
-            "name": "CHBC",
-            "admin_password": "password"
-        });
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (GET) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}) => {
+            let result = db.get_team(team_id)?.get_rule(rule_id).ok_or(DbError::NotFound)?;
 
-        let response = json!(handle_request(
-            &RequestBuilder::post(String::from("/login"), &login_request),
-            &DbMock::default(),
-        )
-        .unwrap());
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}/archive) => {
+            let result = db.archive_rule(team_id, rule_id)?;
 
-        assert_eq!(response["admin_password"], login_request["admin_password"]);
-    }
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}/restore) => {
+            let result = db.restore_rule(team_id, rule_id)?;
 
-    #[test]
-    fn test_login_fails() {
-        let mut login_request = json!({
-            "name": "CHBC",
-        });
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (GET) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}/versions) => {
+            let result = db.get_rule_versions(team_id, rule_id)?;
 
-        let error = handle_request(
-            &RequestBuilder::post(String::from("/login"), &login_request),
-            &DbMock {
-                teams_db: TeamsDbMock::NotFound,
-                ..Default::default()
-            },
-        )
-        .unwrap_err();
+            Ok(ResponseWrapper::RuleVersions(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}) => {
+            let mut input = json_input::<UpdateRuleRequest>(request)?;
+            validate_rule(&input)?;
+            resolve_categories(db, team_id, std::slice::from_mut(&mut input))?;
 
-        assert_eq!(error.kind, ErrorKind::NotFound);
+            if let Some(id) = input.id {
+                if id != rule_id {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The id of the rule {} can't be changed",
+                        rule_id
+                    )));
+                }
+            }
 
-        login_request["admin_password"] = json!("password");
+            let result = db.update_rule(team_id, rule_id, &input.into())?;
 
-        let error = handle_request(
-            &RequestBuilder::post(String::from("/login"), &login_request),
-            &DbMock {
-                teams_db: TeamsDbMock::NotFound,
-                ..Default::default()
-            },
-        )
-        .unwrap_err();
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (DELETE) (/teams/{team_id:Uuid}/rules/{rule_id:Uuid}) => {
+            let result = db.delete_rule(team_id, rule_id)?;
 
-        assert_eq!(error.kind, ErrorKind::NotFound);
-    }
+            Ok(ResponseWrapper::Rule(result))
+        },
+        (GET) (/teams/{team_id:Uuid}/categories) => {
+            let result = db.get_categories(team_id)?;
+
+            Ok(ResponseWrapper::Categories(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/categories) => {
+            let input = json_input::<UpdateCategoryRequest>(request)?;
+            validate_category(&input)?;
+            let input: RuleCategory = (input, team_id).into();
+
+            let result = db.create_category(&input)?;
+
+            Ok(ResponseWrapper::Category(result))
+        },
+        (POST) (/teams/{team_id:Uuid}/categories/{category_id:Uuid}) => {
+            let input = json_input::<UpdateCategoryRequest>(request)?;
+            validate_category(&input)?;
+
+            if let Some(id) = input.id {
+                if id != category_id {
+                    return Err(ErrorResponse::not_valid(format!(
+                        "The id of the category {} can't be changed",
+                        category_id
+                    )));
+                }
+            }
+
+            let input: UpdateCategory = input.into();
+
+            let result = db.update_category(team_id, category_id, &input)?;
+
+            Ok(ResponseWrapper::Category(result))
+        },
+        (DELETE) (/teams/{team_id:Uuid}/categories/{category_id:Uuid}) => {
+            let result = db.delete_category(team_id, category_id)?;
+
+            Ok(ResponseWrapper::Category(result))
+        },
+        (GET) (/teams/{id:Uuid}/export) => {
+            let result = export_team(db, db.get_team(id)?)?;
+
+            Ok(ResponseWrapper::Export(result))
+        },
+        _ => {
+            Err(ErrorResponse::not_found())
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
+    use crate::teams::models::LegacyCategory;
+    use crate::test_utils::routes::{DbMock, TeamsDbMock};
 
     #[test]
     fn test_get_team() {
@@ -178,7 +489,7 @@ mod tests {
                 "description": "This is a description !",
                 "kind": {
                     "type": "TIME_MULTIPLICATION",
-                    "price_per_time_unit": 0.2,
+                    "price_per_time_unit": 20,
                     "time_unit": "MINUTE"
                 }
             }]
@@ -191,6 +502,84 @@ mod tests {
         .unwrap());
 
         assert_eq!(response["id"], json!(team_id));
+        assert_eq!(response["admin_password"], serde_json::Value::Null);
+        assert_eq!(response["currency"], json!("EUR"));
+        assert_eq!(response["locale"], json!("fr-FR"));
+    }
+
+    #[test]
+    fn test_create_team_with_legacy_categories() {
+        let team = json!({
+            "name": "Test_team",
+            "admin_password": "password",
+            "rules": [{
+                "name": "Late",
+                "category": "GAME_DAY",
+                "description": "Late to the game",
+                "kind": {
+                    "type": "BASIC",
+                    "price": 200
+                }
+            }, {
+                "name": "Absent",
+                "description": "Absent from the training",
+                "kind": {
+                    "type": "BASIC",
+                    "price": 500
+                }
+            }]
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(String::from("/teams"), &team),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["rules"][0]["name"], json!("Late"));
+        assert_ne!(response["rules"][0]["category_id"], serde_json::Value::Null);
+        assert_eq!(response["rules"][1]["category_id"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_create_team_with_currency() {
+        let mut team = json!({
+            "name": "Test_team",
+            "admin_password": "password",
+            "rules": [],
+            "currency": "CHF",
+            "locale": "de-CH"
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(String::from("/teams"), &team),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["currency"], json!("CHF"));
+        assert_eq!(response["locale"], json!("de-CH"));
+
+        team["currency"] = json!("EURO");
+
+        let error = handle_request(
+            &RequestBuilder::post(String::from("/teams"), &team),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        team["currency"] = json!("CHF");
+        team["locale"] = json!("de_CH");
+
+        let error = handle_request(
+            &RequestBuilder::post(format!("/teams/{}", Uuid::new_v4()), &team),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
     }
 
     #[test]
@@ -273,4 +662,589 @@ mod tests {
 
         assert_eq!(error.kind, ErrorKind::Unknown);
     }
+
+    #[test]
+    fn test_delete_team() {
+        let id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete_with_data(
+                format!("/teams/{}", id),
+                &json!({ "admin_password": "password", "confirmation": "" }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["team"]["id"], json!(id));
+        assert_eq!(response["team"]["purge_at"], serde_json::Value::Null);
+        assert_eq!(response["users"][0]["team_id"], json!(id));
+        assert_eq!(response["sanctions"][0]["team_id"], json!(id));
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete_with_data(
+                format!("/teams/{}", id),
+                &json!({
+                    "admin_password": "password",
+                    "confirmation": "",
+                    "grace_period_days": 30
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_ne!(response["team"]["purge_at"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_delete_team_fails() {
+        let id = Uuid::new_v4();
+        let url = format!("/teams/{}", id);
+
+        let error = handle_request(
+            &RequestBuilder::delete_with_data(
+                url.clone(),
+                &json!({ "admin_password": "password", "confirmation": "CHBC" }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        let error = handle_request(
+            &RequestBuilder::delete_with_data(
+                url.clone(),
+                &json!({ "admin_password": "wrong", "confirmation": "" }),
+            ),
+            &DbMock {
+                teams_db: TeamsDbMock::WrongPassword,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+
+        let error = handle_request(
+            &RequestBuilder::delete_with_data(
+                url.clone(),
+                &json!({
+                    "admin_password": "password",
+                    "confirmation": "",
+                    "grace_period_days": 365
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.description,
+            "The grace period must be between 0 and 90 days"
+        );
+
+        let error = handle_request(&RequestBuilder::delete(url), &DbMock::default()).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Json);
+    }
+
+    #[test]
+    fn test_export_team() {
+        let id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/export", id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["team"]["id"], json!(id));
+        assert_eq!(response["payments"], json!([]));
+    }
+
+    #[test]
+    fn test_get_rules() {
+        let team_id = Uuid::new_v4();
+        let rule = Rule {
+            id: Uuid::new_v4(),
+            ..Default::default()
+        };
+        let db = DbMock {
+            teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules", team_id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response, json!([rule]));
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules/{}", team_id, rule.id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response, json!(rule));
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules/{}", team_id, Uuid::new_v4())),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_get_rule_versions() {
+        let team_id = Uuid::new_v4();
+        let rule = Rule {
+            id: Uuid::new_v4(),
+            ..Default::default()
+        };
+        let db = DbMock {
+            teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules/{}/versions", team_id, rule.id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["rule_id"], json!(rule.id));
+        assert_eq!(response[0]["version"], json!(1));
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!(
+                "/teams/{}/rules/{}/versions",
+                team_id,
+                Uuid::new_v4()
+            )),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response, json!([]));
+    }
+
+    #[test]
+    fn test_rule_keeps_legacy_category() {
+        let team_id = Uuid::new_v4();
+        let rule = Rule {
+            id: Uuid::new_v4(),
+            name: String::from("Late"),
+            category_id: Some(Uuid::new_v4()),
+            kind: RuleKind::Basic { price: 200 },
+            category: LegacyCategory::GameDay,
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules/{}", team_id, rule.id)),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone()]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(
+            response,
+            json!({
+                "id": rule.id,
+                "name": "Late",
+                "category_id": rule.category_id,
+                "description": "",
+                "kind": {
+                    "type": "BASIC",
+                    "price": 200
+                },
+                "limits": {
+                    "min_price": null,
+                    "max_price": null,
+                    "period_cap": null,
+                    "period_floor": null
+                },
+                "archived_at": null,
+                "category": "GAME_DAY"
+            })
+        );
+    }
+
+    #[test]
+    fn test_create_rule() {
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules", team_id),
+                &json!({
+                    "name": "Late",
+                    "category": "TRAINING_DAY",
+                    "description": "Late to the training",
+                    "kind": {
+                        "type": "BASIC",
+                        "price": 200
+                    }
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["name"], json!("Late"));
+        assert_ne!(response["id"], json!(Uuid::nil()));
+        assert_ne!(response["category_id"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_create_rule_fails() {
+        let team_id = Uuid::new_v4();
+        let rule = json!({
+            "name": "Late",
+            "description": "Late to the training",
+            "kind": {
+                "type": "ESCALATING",
+                "tiers": [],
+                "reset_period": "MONTH"
+            }
+        });
+
+        let error = handle_request(
+            &RequestBuilder::post(format!("/teams/{}/rules", team_id), &rule),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            "The rule Late must have at least one tier"
+        );
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules", team_id),
+                &json!({
+                    "name": "Late",
+                    "description": "Late to the training",
+                    "kind": {
+                        "type": "BASIC",
+                        "price": 200
+                    },
+                    "limits": {
+                        "min_price": 500,
+                        "max_price": 100
+                    }
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.description,
+            "The minimum price of the rule Late is higher than its maximum price"
+        );
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules", team_id),
+                &json!({
+                    "name": "Late",
+                    "description": "Late to the training",
+                    "kind": {
+                        "type": "MULTIPLICATION",
+                        "price_to_multiply": -200
+                    }
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.description,
+            "The prices of the rule Late can't be negative"
+        );
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules", team_id),
+                &json!({
+                    "name": "Late",
+                    "description": "Late to the training",
+                    "kind": {
+                        "type": "BASIC",
+                        "price": 200
+                    },
+                    "limits": {
+                        "period_cap": { "max_price": 1000, "period": "MONTH" },
+                        "period_floor": { "min_price": 2000, "period": "MONTH" }
+                    }
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.description,
+            "The period minimum of the rule Late is higher than its period cap"
+        );
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                String::from("/teams"),
+                &json!({
+                    "name": "Team",
+                    "admin_password": "password",
+                    "rules": [rule]
+                }),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+    }
+
+    #[test]
+    fn test_update_rule() {
+        let team_id = Uuid::new_v4();
+        let rule_id = Uuid::new_v4();
+        let category_id = Uuid::new_v4();
+        let url = format!("/teams/{}/rules/{}", team_id, rule_id);
+        let rule = json!({
+            "name": "Late",
+            "category_id": category_id,
+            "description": "Late to the game",
+            "kind": {
+                "type": "BASIC",
+                "price": 500
+            }
+        });
+        let db = DbMock {
+            teams_db: TeamsDbMock::SuccessWithRules(vec![Rule {
+                id: rule_id,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        let response =
+            json!(handle_request(&RequestBuilder::post(url.clone(), &rule), &db).unwrap());
+
+        assert_eq!(response["id"], json!(rule_id));
+        assert_eq!(response["category_id"], json!(category_id));
+
+        let mut other_rule = rule.clone();
+        other_rule["id"] = json!(Uuid::new_v4());
+
+        let error = handle_request(&RequestBuilder::post(url, &other_rule), &db).unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules/{}", team_id, Uuid::new_v4()),
+                &rule,
+            ),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_delete_rule() {
+        let team_id = Uuid::new_v4();
+        let rule_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/rules/{}", team_id, rule_id)),
+            &DbMock {
+                teams_db: TeamsDbMock::SuccessWithRules(vec![Rule {
+                    id: rule_id,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(rule_id));
+
+        let error = handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/rules/{}", team_id, rule_id)),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_archive_rule() {
+        let team_id = Uuid::new_v4();
+        let rule = Rule {
+            id: Uuid::new_v4(),
+            ..Default::default()
+        };
+        let archived_rule = Rule {
+            id: Uuid::new_v4(),
+            archived_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        let db = DbMock {
+            teams_db: TeamsDbMock::SuccessWithRules(vec![rule.clone(), archived_rule.clone()]),
+            ..Default::default()
+        };
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules/{}/archive", team_id, rule.id),
+                &json!({}),
+            ),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(rule.id));
+        assert!(!response["archived_at"].is_null());
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules/{}/archive", team_id, archived_rule.id),
+                &json!({}),
+            ),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/rules/{}/restore", team_id, archived_rule.id),
+                &json!({}),
+            ),
+            &db,
+        )
+        .unwrap());
+
+        assert!(response["archived_at"].is_null());
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/rules", team_id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response, json!([rule]));
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}?include_archived=true", team_id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["rules"], json!([rule, archived_rule]));
+    }
+
+    #[test]
+    fn test_categories() {
+        let team_id = Uuid::new_v4();
+        let category_id = Uuid::new_v4();
+        let db = DbMock::default();
+
+        let response = json!(handle_request(
+            &RequestBuilder::get(format!("/teams/{}/categories", team_id)),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response[0]["name"], json!("Game day"));
+        assert_eq!(response[1]["name"], json!("Training day"));
+
+        let category = json!({
+            "id": category_id,
+            "name": "Away trip",
+            "color": "#FB8C00",
+            "position": 2,
+        });
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(format!("/teams/{}/categories", team_id), &category),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(category_id));
+        assert_eq!(response["team_id"], json!(team_id));
+        assert_eq!(response["color"], json!("#FB8C00"));
+
+        let url = format!("/teams/{}/categories/{}", team_id, category_id);
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                url.clone(),
+                &json!({
+                    "name": "Team dinner",
+                    "color": "#8E24AA",
+                    "position": 0,
+                }),
+            ),
+            &db,
+        )
+        .unwrap());
+
+        assert_eq!(response["name"], json!("Team dinner"));
+        assert_eq!(response["position"], json!(0));
+
+        let response = json!(handle_request(&RequestBuilder::delete(url.clone()), &db).unwrap());
+
+        assert_eq!(response["id"], json!(category_id));
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                url.clone(),
+                &json!({
+                    "id": Uuid::new_v4(),
+                    "name": "Team dinner",
+                    "color": "#8E24AA",
+                    "position": 0,
+                }),
+            ),
+            &db,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+
+        for color in &["8E24AA", "#8E24A", "#8E24AZ", "purple"] {
+            let error = handle_request(
+                &RequestBuilder::post(
+                    format!("/teams/{}/categories", team_id),
+                    &json!({
+                        "name": "Team dinner",
+                        "color": color,
+                        "position": 0,
+                    }),
+                ),
+                &db,
+            )
+            .unwrap_err();
+
+            assert_eq!(error.kind, ErrorKind::NotValid);
+        }
+    }
 }
@@ -0,0 +1,280 @@
+pub mod password {
+    use bcrypt::{hash, verify, BcryptError, DEFAULT_COST};
+
+    const HASH_COST: u32 = if cfg!(test) { 4 } else { DEFAULT_COST };
+
+    #[derive(Debug, PartialEq)]
+    pub enum PasswordMatch {
+        Hashed,
+        Plaintext,
+        Mismatch,
+    }
+
+    pub fn hash_password(password: &str) -> Result<String, BcryptError> {
+        hash(password, HASH_COST)
+    }
+
+    /// Passwords stored before hashing was introduced are kept as plaintext until
+    /// the next successful login, so a stored value which isn't a bcrypt hash is
+    /// compared as is.
+    pub fn verify_password(password: &str, stored: &str) -> Result<PasswordMatch, BcryptError> {
+        if is_hashed(stored) {
+            match verify(password, stored)? {
+                true => Ok(PasswordMatch::Hashed),
+                false => Ok(PasswordMatch::Mismatch),
+            }
+        } else if password == stored {
+            Ok(PasswordMatch::Plaintext)
+        } else {
+            Ok(PasswordMatch::Mismatch)
+        }
+    }
+
+    fn is_hashed(stored: &str) -> bool {
+        stored.len() == 60
+            && ["$2a$", "$2b$", "$2x$", "$2y$"]
+                .iter()
+                .any(|prefix| stored.starts_with(prefix))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_verify_hashed_password() {
+            let stored = hash_password("password").unwrap();
+
+            assert_ne!(stored, "password");
+            assert_eq!(
+                verify_password("password", &stored).unwrap(),
+                PasswordMatch::Hashed
+            );
+            assert_eq!(
+                verify_password("wrong", &stored).unwrap(),
+                PasswordMatch::Mismatch
+            );
+        }
+
+        #[test]
+        fn test_verify_plaintext_password() {
+            assert_eq!(
+                verify_password("password", "password").unwrap(),
+                PasswordMatch::Plaintext
+            );
+            assert_eq!(
+                verify_password("wrong", "password").unwrap(),
+                PasswordMatch::Mismatch
+            );
+        }
+    }
+}
+
+pub mod currency {
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    use super::super::{interface::TeamsDb, models::Team};
+    use crate::database::postgres::DbError;
+
+    /// Active ISO 4217 codes with the number of digits of their minor unit.
+    #[rustfmt::skip]
+    const CURRENCIES: &[(&str, u32)] = &[
+        ("AED", 2), ("AFN", 2), ("ALL", 2), ("AMD", 2), ("ANG", 2), ("AOA", 2), ("ARS", 2),
+        ("AUD", 2), ("AWG", 2), ("AZN", 2), ("BAM", 2), ("BBD", 2), ("BDT", 2), ("BGN", 2),
+        ("BHD", 3), ("BIF", 0), ("BMD", 2), ("BND", 2), ("BOB", 2), ("BRL", 2), ("BSD", 2),
+        ("BTN", 2), ("BWP", 2), ("BYN", 2), ("BZD", 2), ("CAD", 2), ("CDF", 2), ("CHF", 2),
+        ("CLP", 0), ("CNY", 2), ("COP", 2), ("CRC", 2), ("CUP", 2), ("CVE", 2), ("CZK", 2),
+        ("DJF", 0), ("DKK", 2), ("DOP", 2), ("DZD", 2), ("EGP", 2), ("ERN", 2), ("ETB", 2),
+        ("EUR", 2), ("FJD", 2), ("FKP", 2), ("GBP", 2), ("GEL", 2), ("GHS", 2), ("GIP", 2),
+        ("GMD", 2), ("GNF", 0), ("GTQ", 2), ("GYD", 2), ("HKD", 2), ("HNL", 2), ("HTG", 2),
+        ("HUF", 2), ("IDR", 2), ("ILS", 2), ("INR", 2), ("IQD", 3), ("IRR", 2), ("ISK", 0),
+        ("JMD", 2), ("JOD", 3), ("JPY", 0), ("KES", 2), ("KGS", 2), ("KHR", 2), ("KMF", 0),
+        ("KPW", 2), ("KRW", 0), ("KWD", 3), ("KYD", 2), ("KZT", 2), ("LAK", 2), ("LBP", 2),
+        ("LKR", 2), ("LRD", 2), ("LSL", 2), ("LYD", 3), ("MAD", 2), ("MDL", 2), ("MGA", 2),
+        ("MKD", 2), ("MMK", 2), ("MNT", 2), ("MOP", 2), ("MRU", 2), ("MUR", 2), ("MVR", 2),
+        ("MWK", 2), ("MXN", 2), ("MYR", 2), ("MZN", 2), ("NAD", 2), ("NGN", 2), ("NIO", 2),
+        ("NOK", 2), ("NPR", 2), ("NZD", 2), ("OMR", 3), ("PAB", 2), ("PEN", 2), ("PGK", 2),
+        ("PHP", 2), ("PKR", 2), ("PLN", 2), ("PYG", 0), ("QAR", 2), ("RON", 2), ("RSD", 2),
+        ("RUB", 2), ("RWF", 0), ("SAR", 2), ("SBD", 2), ("SCR", 2), ("SDG", 2), ("SEK", 2),
+        ("SGD", 2), ("SHP", 2), ("SLE", 2), ("SOS", 2), ("SRD", 2), ("SSP", 2), ("STN", 2),
+        ("SVC", 2), ("SYP", 2), ("SZL", 2), ("THB", 2), ("TJS", 2), ("TMT", 2), ("TND", 3),
+        ("TOP", 2), ("TRY", 2), ("TTD", 2), ("TWD", 2), ("TZS", 2), ("UAH", 2), ("UGX", 0),
+        ("USD", 2), ("UYU", 2), ("UZS", 2), ("VES", 2), ("VND", 0), ("VUV", 0), ("WST", 2),
+        ("XAF", 0), ("XCD", 2), ("XOF", 0), ("XPF", 0), ("YER", 2), ("ZAR", 2), ("ZMW", 2),
+        ("ZWL", 2),
+    ];
+
+    pub fn is_currency(code: &str) -> bool {
+        CURRENCIES.iter().any(|(currency, _)| *currency == code)
+    }
+
+    /// Accepts a language, optionally followed by a region, such as `fr` or
+    /// `de-CH`.
+    pub fn is_locale(locale: &str) -> bool {
+        let mut parts = locale.split('-');
+
+        let language = parts.next().unwrap_or_default();
+        let region = parts.next();
+
+        (2..=3).contains(&language.len())
+            && language.chars().all(|c| c.is_ascii_lowercase())
+            && match region {
+                Some(region) => region.len() == 2 && region.chars().all(|c| c.is_ascii_uppercase()),
+                None => true,
+            }
+            && parts.next().is_none()
+    }
+
+    /// Implemented by the responses holding amounts of money, in minor units.
+    pub trait Priced {
+        fn amounts(&self) -> Vec<(&'static str, i64)>;
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct WithCurrency<T> {
+        #[serde(flatten)]
+        pub data: T,
+        pub currency: String,
+        /// The amounts of `data` formatted for the locale of the team, by field.
+        pub formatted: BTreeMap<&'static str, String>,
+    }
+
+    pub struct Currency {
+        pub code: String,
+        pub locale: String,
+    }
+
+    impl From<&Team> for Currency {
+        fn from(team: &Team) -> Currency {
+            Currency {
+                code: team.currency.clone(),
+                locale: team.locale.clone(),
+            }
+        }
+    }
+
+    impl Currency {
+        pub fn of_team<T: TeamsDb>(db: &T, team_id: Uuid) -> Result<Currency, DbError> {
+            Ok(Currency::from(&db.get_team(team_id)?))
+        }
+
+        pub fn apply<T: Priced>(&self, data: T) -> WithCurrency<T> {
+            WithCurrency {
+                formatted: data
+                    .amounts()
+                    .into_iter()
+                    .map(|(field, amount)| (field, self.format(amount)))
+                    .collect(),
+                currency: self.code.clone(),
+                data,
+            }
+        }
+
+        pub fn apply_all<T: Priced>(&self, data: Vec<T>) -> Vec<WithCurrency<T>> {
+            data.into_iter().map(|value| self.apply(value)).collect()
+        }
+
+        pub fn format(&self, amount: i64) -> String {
+            let digits = CURRENCIES
+                .iter()
+                .find(|(currency, _)| *currency == self.code)
+                .map_or(2, |(_, digits)| *digits);
+            let language = self.locale.split('-').next().unwrap_or_default();
+
+            let (decimal_separator, group_separator) = match self.locale.as_str() {
+                "de-CH" | "fr-CH" | "it-CH" => (".", "'"),
+                _ => match language {
+                    "fr" => (",", "\u{202f}"),
+                    "de" | "es" | "it" | "nl" | "pt" => (",", "."),
+                    _ => (".", ","),
+                },
+            };
+
+            let absolute = amount.unsigned_abs();
+            let divisor = 10_u64.pow(digits);
+            let units = (absolute / divisor).to_string();
+
+            let mut number = String::new();
+            for (i, digit) in units.chars().enumerate() {
+                if i > 0 && (units.len() - i).is_multiple_of(3) {
+                    number.push_str(group_separator);
+                }
+                number.push(digit);
+            }
+            if digits > 0 {
+                number = format!(
+                    "{}{}{:0width$}",
+                    number,
+                    decimal_separator,
+                    absolute % divisor,
+                    width = digits as usize
+                );
+            }
+
+            let sign = if amount < 0 { "-" } else { "" };
+            let symbol = match self.code.as_str() {
+                "EUR" => "€",
+                "USD" => "$",
+                "GBP" => "£",
+                "JPY" => "¥",
+                code => code,
+            };
+
+            let is_code = symbol == self.code;
+
+            if language == "en" && !is_code {
+                format!("{}{}{}", sign, symbol, number)
+            } else if is_code && (language == "en" || self.locale.ends_with("-CH")) {
+                format!("{}{} {}", sign, symbol, number)
+            } else {
+                format!("{}{} {}", sign, number, symbol)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn currency(code: &str, locale: &str) -> Currency {
+            Currency {
+                code: String::from(code),
+                locale: String::from(locale),
+            }
+        }
+
+        #[test]
+        fn test_is_currency() {
+            assert!(is_currency("EUR"));
+            assert!(is_currency("CHF"));
+            assert!(!is_currency("eur"));
+            assert!(!is_currency("EURO"));
+        }
+
+        #[test]
+        fn test_is_locale() {
+            assert!(is_locale("fr"));
+            assert!(is_locale("fr-FR"));
+            assert!(is_locale("de-CH"));
+            assert!(!is_locale("FR"));
+            assert!(!is_locale("fr_FR"));
+            assert!(!is_locale("fr-FR-x"));
+        }
+
+        #[test]
+        fn test_format() {
+            assert_eq!(currency("EUR", "fr-FR").format(1499), "14,99 €");
+            assert_eq!(
+                currency("EUR", "fr-FR").format(123_456_789),
+                "1\u{202f}234\u{202f}567,89 €"
+            );
+            assert_eq!(currency("EUR", "de-DE").format(-5), "-0,05 €");
+            assert_eq!(currency("CHF", "de-CH").format(123_450), "CHF 1'234.50");
+            assert_eq!(currency("USD", "en-US").format(1000), "$10.00");
+            assert_eq!(currency("JPY", "en").format(1500), "¥1,500");
+            assert_eq!(currency("KWD", "fr").format(1500), "1,500 KWD");
+        }
+    }
+}
@@ -1,10 +1,36 @@
-use crate::database::postgres::{init_db_connection, DbConnection};
+use crate::database::postgres::{get_db_connection, init_db_pool, DbConnection, PoolConfig};
+use crate::teams::{
+    interface::TeamsDb,
+    models::{Rule, Team},
+};
 use dotenv::dotenv;
 use std::env::var;
+use uuid::Uuid;
 
 pub fn init_connection() -> DbConnection {
     dotenv().ok();
 
     let database_url = var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
-    init_db_connection(&database_url).expect("Something went wrong while getting the connection")
+    let pool = init_db_pool(
+        &database_url,
+        &PoolConfig {
+            max_size: 1,
+            ..Default::default()
+        },
+    );
+
+    get_db_connection(&pool).expect("Something went wrong while getting the connection")
+}
+
+/// Creates the default team with the default rule, which sanctions can refer to.
+pub fn create_team_with_rule<T>(db: &T) -> Uuid
+where
+    T: TeamsDb,
+{
+    db.create_team(&Team {
+        rules: vec![Rule::default()],
+        ..Default::default()
+    })
+    .unwrap()
+    .id
 }
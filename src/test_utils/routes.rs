@@ -1,8 +1,15 @@
-use chrono::{naive::NaiveDate, Local};
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Duration, Local, Utc,
+};
 use uuid::Uuid;
 
+use crate::audit::{interface::AuditDb, models::*};
 use crate::database::postgres::DbError;
+use crate::payments::{interface::PaymentsDb, models::*};
 use crate::sanctions::{interface::SanctionsDb, models::*};
+use crate::scheduler::{interface::MonthlyRunsDb, models::*};
+use crate::sessions::{interface::SessionsDb, models::*};
 use crate::teams::{interface::TeamsDb, models::*};
 use crate::users::{interface::UsersDb, models::*};
 
@@ -11,11 +18,23 @@ pub struct DbMock {
     pub teams_db: TeamsDbMock,
     pub users_db: UsersDbMock,
     pub sanctions_db: SanctionsDbMock,
+    pub sessions_db: SessionsDbMock,
+    pub payments_db: PaymentsDbMock,
+    pub monthly_runs_db: MonthlyRunsDbMock,
+}
+
+pub fn session_config() -> SessionConfig {
+    SessionConfig {
+        secret: String::from("secret"),
+        lifetime: Duration::hours(1),
+    }
 }
 
 pub enum TeamsDbMock {
     Success,
     SuccessWithRules(Vec<Rule>),
+    PendingDeletion,
+    WrongPassword,
     NotFound,
     Unknown,
 }
@@ -26,18 +45,41 @@ impl Default for TeamsDbMock {
     }
 }
 
+/// The mocked rules have a single version, which shares their id.
+fn first_version(team_id: Uuid, rule: Rule) -> RuleVersion {
+    RuleVersion {
+        id: rule.id,
+        rule_id: rule.id,
+        team_id,
+        version: 1,
+        name: rule.name,
+        category_id: rule.category_id,
+        description: rule.description,
+        kind: rule.kind,
+        limits: rule.limits,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
 impl TeamsDb for DbMock {
     fn login(&self, _name: &str, _admin_password: &Option<String>) -> Result<Uuid, DbError> {
         match self.teams_db {
             TeamsDbMock::Success => Ok(Uuid::new_v4()),
-            TeamsDbMock::NotFound => Err(DbError::NotFound),
+            TeamsDbMock::WrongPassword | TeamsDbMock::NotFound => Err(DbError::NotFound),
             _ => unimplemented!(),
         }
     }
 
+    fn get_team_ids(&self) -> Result<Vec<Uuid>, DbError> {
+        match self.teams_db {
+            TeamsDbMock::Unknown => Err(DbError::Unknown),
+            _ => Ok(vec![Uuid::new_v4()]),
+        }
+    }
+
     fn get_team(&self, id: Uuid) -> Result<Team, DbError> {
         match &self.teams_db {
-            TeamsDbMock::Success => Ok(Team {
+            TeamsDbMock::Success | TeamsDbMock::WrongPassword => Ok(Team {
                 id,
                 ..Default::default()
             }),
@@ -46,6 +88,11 @@ impl TeamsDb for DbMock {
                 rules: rules.clone(),
                 ..Default::default()
             }),
+            TeamsDbMock::PendingDeletion => Ok(Team {
+                id,
+                purge_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            }),
             TeamsDbMock::NotFound => Err(DbError::NotFound),
             TeamsDbMock::Unknown => Err(DbError::Unknown),
         }
@@ -66,16 +113,157 @@ impl TeamsDb for DbMock {
                 name: team.name.clone(),
                 admin_password: team.admin_password.clone(),
                 rules: team.rules.clone(),
+                currency: team
+                    .currency
+                    .clone()
+                    .unwrap_or_else(|| String::from(DEFAULT_CURRENCY)),
+                locale: team
+                    .locale
+                    .clone()
+                    .unwrap_or_else(|| String::from(DEFAULT_LOCALE)),
+                purge_at: None,
             }),
             TeamsDbMock::NotFound => Err(DbError::NotFound),
             TeamsDbMock::Unknown => Err(DbError::Unknown),
             _ => unimplemented!(),
         }
     }
+
+    fn create_rule(&self, team_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+        self.get_team(team_id)?;
+
+        Ok(rule.clone())
+    }
+
+    fn update_rule(&self, team_id: Uuid, rule_id: Uuid, rule: &Rule) -> Result<Rule, DbError> {
+        self.get_team(team_id)?
+            .get_rule(rule_id)
+            .ok_or(DbError::NotFound)?;
+
+        Ok(Rule {
+            id: rule_id,
+            ..rule.clone()
+        })
+    }
+
+    fn delete_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        self.get_team(team_id)?
+            .get_rule(rule_id)
+            .ok_or(DbError::NotFound)
+    }
+
+    fn archive_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        match self.get_team(team_id)?.get_rule(rule_id) {
+            Some(rule) if !rule.is_archived() => Ok(Rule {
+                archived_at: Some(Utc::now().naive_utc()),
+                ..rule
+            }),
+            _ => Err(DbError::NotFound),
+        }
+    }
+
+    fn restore_rule(&self, team_id: Uuid, rule_id: Uuid) -> Result<Rule, DbError> {
+        match self.get_team(team_id)?.get_rule(rule_id) {
+            Some(rule) if rule.is_archived() => Ok(Rule {
+                archived_at: None,
+                ..rule
+            }),
+            _ => Err(DbError::NotFound),
+        }
+    }
+
+    fn get_rule_versions(&self, team_id: Uuid, rule_id: Uuid) -> Result<Vec<RuleVersion>, DbError> {
+        Ok(self
+            .get_team(team_id)?
+            .get_rule(rule_id)
+            .map(|rule| first_version(team_id, rule))
+            .into_iter()
+            .collect())
+    }
+
+    fn get_rule_version(&self, team_id: Uuid, version_id: Uuid) -> Result<RuleVersion, DbError> {
+        self.get_team(team_id)?
+            .get_rule(version_id)
+            .map(|rule| first_version(team_id, rule))
+            .ok_or(DbError::NotFound)
+    }
+
+    fn get_categories(&self, team_id: Uuid) -> Result<Vec<RuleCategory>, DbError> {
+        self.get_team(team_id)?;
+
+        Ok(DEFAULT_CATEGORIES
+            .iter()
+            .enumerate()
+            .map(|(position, (name, color))| RuleCategory {
+                id: Uuid::new_v4(),
+                team_id,
+                name: String::from(*name),
+                color: String::from(*color),
+                position: position as i32,
+            })
+            .collect())
+    }
+
+    fn create_category(&self, category: &RuleCategory) -> Result<RuleCategory, DbError> {
+        self.get_team(category.team_id)?;
+
+        Ok(category.clone())
+    }
+
+    fn update_category(
+        &self,
+        team_id: Uuid,
+        category_id: Uuid,
+        category: &UpdateCategory,
+    ) -> Result<RuleCategory, DbError> {
+        self.get_team(team_id)?;
+
+        Ok(RuleCategory {
+            id: category_id,
+            team_id,
+            name: category.name.clone(),
+            color: category.color.clone(),
+            position: category.position,
+        })
+    }
+
+    fn delete_category(&self, team_id: Uuid, category_id: Uuid) -> Result<RuleCategory, DbError> {
+        self.get_team(team_id)?;
+
+        Ok(RuleCategory {
+            id: category_id,
+            team_id,
+            ..Default::default()
+        })
+    }
+
+    fn delete_team(&self, id: Uuid) -> Result<Team, DbError> {
+        self.get_team(id)
+    }
+
+    fn schedule_team_deletion(&self, id: Uuid, purge_at: NaiveDateTime) -> Result<Team, DbError> {
+        Ok(Team {
+            purge_at: Some(purge_at),
+            ..self.get_team(id)?
+        })
+    }
+
+    fn restore_team(&self, id: Uuid) -> Result<Team, DbError> {
+        self.get_team(id)
+    }
+
+    fn purge_teams(&self) -> Result<Vec<Team>, DbError> {
+        match self.teams_db {
+            TeamsDbMock::Unknown => Err(DbError::Unknown),
+            _ => Ok(vec![]),
+        }
+    }
 }
 
 pub enum UsersDbMock {
     Success,
+    SuccessWithRole(Role),
+    Deactivated,
     NotFound,
     UnexistingTeam,
     DuplicatedField,
@@ -88,7 +276,7 @@ impl Default for UsersDbMock {
 }
 
 impl UsersDb for DbMock {
-    fn get_users(&self, team_id: Uuid) -> Result<Vec<User>, DbError> {
+    fn get_users(&self, team_id: Uuid, _include_deactivated: bool) -> Result<Vec<User>, DbError> {
         match self.users_db {
             UsersDbMock::Success => Ok(vec![User {
                 team_id,
@@ -105,6 +293,18 @@ impl UsersDb for DbMock {
                 team_id,
                 ..Default::default()
             }),
+            UsersDbMock::SuccessWithRole(role) => Ok(User {
+                id: user_id,
+                team_id,
+                role,
+                ..Default::default()
+            }),
+            UsersDbMock::Deactivated => Ok(User {
+                id: user_id,
+                team_id,
+                deactivated_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            }),
             UsersDbMock::NotFound => Err(DbError::NotFound),
             _ => unimplemented!(),
         }
@@ -112,7 +312,7 @@ impl UsersDb for DbMock {
 
     fn create_user(&self, user: &User) -> Result<User, DbError> {
         match self.users_db {
-            UsersDbMock::Success => Ok(user.clone()),
+            UsersDbMock::Success | UsersDbMock::SuccessWithRole(_) => Ok(user.clone()),
             UsersDbMock::UnexistingTeam => Err(DbError::ForeignKeyViolation(String::from("Error"))),
             UsersDbMock::DuplicatedField => Err(DbError::UniqueViolation(String::from("Error"))),
             _ => unimplemented!(),
@@ -133,15 +333,61 @@ impl UsersDb for DbMock {
                 lastname: user.lastname.clone(),
                 nickname: user.nickname.clone(),
                 email: user.email.clone(),
+                role: user.role.unwrap_or_default(),
+                password: None,
+                deactivated_at: None,
             }),
             UsersDbMock::NotFound => Err(DbError::NotFound),
             _ => unimplemented!(),
         }
     }
+
+    fn deactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        match self.users_db {
+            UsersDbMock::Success => Ok(User {
+                id: user_id,
+                team_id,
+                deactivated_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            }),
+            UsersDbMock::Deactivated | UsersDbMock::NotFound => Err(DbError::NotFound),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn reactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        match self.users_db {
+            UsersDbMock::Deactivated => Ok(User {
+                id: user_id,
+                team_id,
+                ..Default::default()
+            }),
+            UsersDbMock::Success | UsersDbMock::NotFound => Err(DbError::NotFound),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn delete_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        self.get_user(team_id, user_id)
+    }
+
+    fn login_user(&self, team_id: Uuid, email: &str, _password: &str) -> Result<User, DbError> {
+        match self.users_db {
+            UsersDbMock::Success => Ok(User {
+                id: Uuid::new_v4(),
+                team_id,
+                email: Some(email.to_string()),
+                ..Default::default()
+            }),
+            UsersDbMock::Deactivated | UsersDbMock::NotFound => Err(DbError::NotFound),
+            _ => unimplemented!(),
+        }
+    }
 }
 
 pub enum SanctionsDbMock {
     Success,
+    SuccessWithSanction(Box<Sanction>),
     NotFound,
 }
 
@@ -156,8 +402,9 @@ impl SanctionsDb for DbMock {
         &self,
         team_id: Uuid,
         date_interval: Option<(NaiveDate, NaiveDate)>,
+        _include_deleted: bool,
     ) -> Result<Vec<Sanction>, DbError> {
-        match self.sanctions_db {
+        match &self.sanctions_db {
             SanctionsDbMock::Success => {
                 let basic_result = vec![
                     Sanction {
@@ -187,13 +434,33 @@ impl SanctionsDb for DbMock {
                     None => basic_result,
                 })
             }
+            SanctionsDbMock::SuccessWithSanction(sanction) => Ok(vec![Sanction {
+                team_id,
+                ..(**sanction).clone()
+            }]),
             _ => unimplemented!(),
         }
     }
 
+    fn get_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+        match &self.sanctions_db {
+            SanctionsDbMock::Success => Ok(Sanction {
+                id: sanction_id,
+                team_id,
+                ..Default::default()
+            }),
+            SanctionsDbMock::SuccessWithSanction(sanction) => Ok(Sanction {
+                id: sanction_id,
+                team_id,
+                ..(**sanction).clone()
+            }),
+            SanctionsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+
     fn create_sanctions(&self, sanctions: &[CreateSanction]) -> Result<Vec<Sanction>, DbError> {
         match self.sanctions_db {
-            SanctionsDbMock::Success => Ok(sanctions
+            SanctionsDbMock::Success | SanctionsDbMock::SuccessWithSanction(_) => Ok(sanctions
                 .iter()
                 .map(|create_sanction| Sanction {
                     id: create_sanction.id,
@@ -204,20 +471,255 @@ impl SanctionsDb for DbMock {
                     created_at: create_sanction
                         .created_at
                         .unwrap_or_else(|| Local::today().naive_local()),
+                    charged_month: None,
+                    applied_limit: create_sanction.applied_limit,
+                    deleted_at: None,
+                    appeal_status: None,
+                    appeal_reason: None,
+                    appeal_comment: None,
+                    approval_status: create_sanction.approval_status,
+                    proposed_by: create_sanction.proposed_by,
+                    rule_id: Some(create_sanction.sanction_info.associated_rule),
+                    rule_version_id: None,
                 })
                 .collect()),
             SanctionsDbMock::NotFound => Err(DbError::ForeignKeyViolation(String::from("Error"))),
         }
     }
 
+    fn update_sanction(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        sanction: &UpdateSanction,
+    ) -> Result<Sanction, DbError> {
+        match self.sanctions_db {
+            SanctionsDbMock::Success | SanctionsDbMock::SuccessWithSanction(_) => Ok(Sanction {
+                id: sanction_id,
+                user_id: sanction.user_id,
+                team_id,
+                sanction_info: sanction.sanction_info.clone(),
+                price: sanction.price,
+                created_at: sanction.created_at,
+                charged_month: None,
+                applied_limit: sanction.applied_limit,
+                deleted_at: None,
+                appeal_status: None,
+                appeal_reason: None,
+                appeal_comment: None,
+                approval_status: ApprovalStatus::Approved,
+                proposed_by: None,
+                rule_id: Some(sanction.sanction_info.associated_rule),
+                rule_version_id: None,
+            }),
+            SanctionsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+
+    fn update_appeal(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        appeal: &UpdateAppeal,
+    ) -> Result<Sanction, DbError> {
+        let sanction = self.get_sanction(team_id, sanction_id)?;
+
+        Ok(Sanction {
+            price: appeal.price.unwrap_or(sanction.price),
+            appeal_status: Some(appeal.appeal_status),
+            appeal_reason: appeal
+                .appeal_reason
+                .clone()
+                .or(sanction.appeal_reason.clone()),
+            appeal_comment: appeal
+                .appeal_comment
+                .clone()
+                .or(sanction.appeal_comment.clone()),
+            ..sanction
+        })
+    }
+
+    fn update_approval(
+        &self,
+        team_id: Uuid,
+        sanction_id: Uuid,
+        approval: &UpdateApproval,
+    ) -> Result<Sanction, DbError> {
+        let sanction = self.get_sanction(team_id, sanction_id)?;
+
+        Ok(Sanction {
+            approval_status: approval.approval_status,
+            price: approval.price,
+            applied_limit: approval.applied_limit,
+            ..sanction
+        })
+    }
+
     fn delete_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
         match self.sanctions_db {
-            SanctionsDbMock::Success => Ok(Sanction {
+            SanctionsDbMock::Success | SanctionsDbMock::SuccessWithSanction(_) => Ok(Sanction {
                 id: sanction_id,
                 team_id,
+                deleted_at: Some(NaiveDate::from_ymd(2019, 10, 5).and_hms(12, 0, 0)),
                 ..Default::default()
             }),
             SanctionsDbMock::NotFound => Err(DbError::NotFound),
         }
     }
+
+    fn restore_sanction(&self, team_id: Uuid, sanction_id: Uuid) -> Result<Sanction, DbError> {
+        match self.sanctions_db {
+            SanctionsDbMock::Success | SanctionsDbMock::SuccessWithSanction(_) => Ok(Sanction {
+                id: sanction_id,
+                team_id,
+                ..Default::default()
+            }),
+            SanctionsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+}
+
+#[derive(Default)]
+pub enum SessionsDbMock {
+    #[default]
+    Success,
+    NotFound,
+}
+
+impl SessionsDb for DbMock {
+    fn get_session(&self, id: Uuid) -> Result<Session, DbError> {
+        match self.sessions_db {
+            SessionsDbMock::Success => Ok(Session {
+                id,
+                ..Default::default()
+            }),
+            SessionsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+
+    fn create_session(&self, session: &Session) -> Result<Session, DbError> {
+        match self.sessions_db {
+            SessionsDbMock::Success => Ok(session.clone()),
+            SessionsDbMock::NotFound => Err(DbError::ForeignKeyViolation(String::from("Error"))),
+        }
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<Session, DbError> {
+        match self.sessions_db {
+            SessionsDbMock::Success => Ok(Session {
+                id,
+                ..Default::default()
+            }),
+            SessionsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+}
+
+#[derive(Default)]
+pub enum PaymentsDbMock {
+    #[default]
+    Success,
+    SuccessWithPayments(Vec<Payment>),
+    NotFound,
+}
+
+impl PaymentsDb for DbMock {
+    fn get_payments(&self, team_id: Uuid, user_id: Option<Uuid>) -> Result<Vec<Payment>, DbError> {
+        match &self.payments_db {
+            PaymentsDbMock::Success => Ok(vec![]),
+            PaymentsDbMock::SuccessWithPayments(payments) => Ok(payments
+                .iter()
+                .map(|payment| Payment {
+                    team_id,
+                    user_id: user_id.unwrap_or(payment.user_id),
+                    ..payment.clone()
+                })
+                .collect()),
+            PaymentsDbMock::NotFound => Err(DbError::NotFound),
+        }
+    }
+
+    fn create_payments(&self, payments: &[CreatePayment]) -> Result<Vec<Payment>, DbError> {
+        match self.payments_db {
+            PaymentsDbMock::NotFound => Err(DbError::ForeignKeyViolation(String::from("Error"))),
+            _ => Ok(payments
+                .iter()
+                .map(|create_payment| Payment {
+                    id: create_payment.id,
+                    team_id: create_payment.team_id,
+                    user_id: create_payment.user_id,
+                    sanction_id: create_payment.sanction_id,
+                    amount: create_payment.amount,
+                    created_at: create_payment
+                        .created_at
+                        .unwrap_or_else(|| Local::today().naive_local()),
+                })
+                .collect()),
+        }
+    }
+
+    fn get_balances(
+        &self,
+        _team_id: Uuid,
+        user_id: Option<Uuid>,
+        _date_interval: Option<(NaiveDate, NaiveDate)>,
+    ) -> Result<Vec<Balance>, DbError> {
+        match self.payments_db {
+            PaymentsDbMock::NotFound => Ok(vec![]),
+            _ => Ok(vec![Balance {
+                user_id: user_id.unwrap_or_else(Uuid::new_v4),
+                total_owed: 500,
+                total_paid: 200,
+                outstanding: 300,
+            }]),
+        }
+    }
+}
+
+#[derive(Default)]
+pub enum MonthlyRunsDbMock {
+    #[default]
+    Success,
+    SuccessWithRun(NaiveDate),
+}
+
+impl MonthlyRunsDb for DbMock {
+    fn get_monthly_runs(&self, team_id: Uuid) -> Result<Vec<MonthlyRun>, DbError> {
+        match self.monthly_runs_db {
+            MonthlyRunsDbMock::Success => Ok(vec![]),
+            MonthlyRunsDbMock::SuccessWithRun(month) => Ok(vec![MonthlyRun::new(team_id, month)]),
+        }
+    }
+
+    fn create_monthly_run(&self, run: &MonthlyRun) -> Result<MonthlyRun, DbError> {
+        Ok(run.clone())
+    }
+}
+
+impl AuditDb for DbMock {
+    fn get_audit_events(
+        &self,
+        team_id: Uuid,
+        _filter: &AuditFilter,
+    ) -> Result<Vec<AuditEvent>, DbError> {
+        Ok(vec![AuditEvent {
+            team_id,
+            ..Default::default()
+        }])
+    }
+
+    fn count_audit_events(&self, _team_id: Uuid, _filter: &AuditFilter) -> Result<i64, DbError> {
+        Ok(1)
+    }
+
+    fn create_audit_events(&self, events: &[AuditEvent]) -> Result<Vec<AuditEvent>, DbError> {
+        Ok(events.to_vec())
+    }
+
+    fn transaction<R, F>(&self, f: F) -> Result<R, DbError>
+    where
+        F: FnOnce() -> Result<R, DbError>,
+    {
+        f()
+    }
 }
@@ -4,7 +4,7 @@ use super::models::{UpdateUser, User};
 use crate::database::postgres::DbError;
 
 pub trait UsersDb {
-    fn get_users(&self, team_id: Uuid) -> Result<Vec<User>, DbError>;
+    fn get_users(&self, team_id: Uuid, include_deactivated: bool) -> Result<Vec<User>, DbError>;
 
     fn get_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError>;
 
@@ -12,4 +12,13 @@ pub trait UsersDb {
 
     fn update_user(&self, team_id: Uuid, user_id: Uuid, user: &UpdateUser)
         -> Result<User, DbError>;
+
+    fn deactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError>;
+
+    fn reactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError>;
+
+    /// Removes the user along with their sanctions and payments.
+    fn delete_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError>;
+
+    fn login_user(&self, team_id: Uuid, email: &str, password: &str) -> Result<User, DbError>;
 }
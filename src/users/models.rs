@@ -1,8 +1,10 @@
-use diesel::{Insertable, Queryable};
+use chrono::NaiveDateTime;
+use diesel::{sql_types::Varchar, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::database::schema::users;
+use crate::varchar_enum;
 
 #[derive(Serialize, Deserialize)]
 pub struct UpdateUserRequest {
@@ -11,6 +13,8 @@ pub struct UpdateUserRequest {
     pub lastname: String,
     pub nickname: Option<String>,
     pub email: Option<String>,
+    pub role: Option<Role>,
+    pub password: Option<String>,
 }
 
 impl From<(UpdateUserRequest, Uuid)> for User {
@@ -22,6 +26,9 @@ impl From<(UpdateUserRequest, Uuid)> for User {
             lastname: update_request.lastname,
             nickname: update_request.nickname,
             email: update_request.email,
+            role: update_request.role.unwrap_or_default(),
+            password: update_request.password,
+            deactivated_at: None,
         }
     }
 }
@@ -33,6 +40,8 @@ impl From<UpdateUserRequest> for UpdateUser {
             lastname: update_request.lastname,
             nickname: update_request.nickname,
             email: update_request.email,
+            role: update_request.role,
+            password: update_request.password,
         }
     }
 }
@@ -46,8 +55,22 @@ pub struct User {
     pub lastname: String,
     pub nickname: Option<String>,
     pub email: Option<String>,
+    pub role: Role,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    /// Deactivated users keep their sanctions and payments, but are left out of
+    /// the roster and can't receive new sanctions.
+    pub deactivated_at: Option<NaiveDateTime>,
 }
 
+impl User {
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+}
+
+/// `None` fields are left unchanged, so the role and the password are only
+/// updated when they are explicitly given.
 #[derive(AsChangeset, Default)]
 #[table_name = "users"]
 pub struct UpdateUser {
@@ -55,4 +78,47 @@ pub struct UpdateUser {
     pub lastname: String,
     pub nickname: Option<String>,
     pub email: Option<String>,
+    pub role: Option<Role>,
+    pub password: Option<String>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsExpression, FromSqlRow, Default,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sql_type = "Varchar"]
+pub enum Role {
+    #[default]
+    Member,
+    Treasurer,
+    Admin,
+}
+
+varchar_enum!(Role);
+
+impl Role {
+    pub fn can(self, permission: Permission) -> bool {
+        match self {
+            Role::Admin => true,
+            Role::Treasurer => matches!(
+                permission,
+                Permission::CreateSanctions
+                    | Permission::ProposeSanctions
+                    | Permission::DeleteSanctions
+                    | Permission::RecordPayments
+            ),
+            Role::Member => matches!(permission, Permission::ProposeSanctions),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    CreateSanctions,
+    ProposeSanctions,
+    DeleteSanctions,
+    RecordPayments,
+    EditRules,
+    EditUsers,
+    ManageTeam,
 }
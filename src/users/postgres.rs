@@ -1,3 +1,4 @@
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use std::ops::Deref;
 use uuid::Uuid;
@@ -10,12 +11,24 @@ use crate::database::{
     postgres::{DbConnection, DbError},
     schema::users,
 };
+use crate::teams::utils::password::{hash_password, verify_password, PasswordMatch};
+
+fn hash_optional_password(password: &Option<String>) -> Result<Option<String>, DbError> {
+    match password {
+        Some(password) => Ok(Some(hash_password(password)?)),
+        None => Ok(None),
+    }
+}
 
 impl UsersDb for DbConnection {
-    fn get_users(&self, team_id: Uuid) -> Result<Vec<User>, DbError> {
-        let users: Vec<User> = users::table
-            .filter(users::team_id.eq(team_id))
-            .get_results(self.deref())?;
+    fn get_users(&self, team_id: Uuid, include_deactivated: bool) -> Result<Vec<User>, DbError> {
+        let mut query = users::table.filter(users::team_id.eq(team_id)).into_boxed();
+
+        if !include_deactivated {
+            query = query.filter(users::deactivated_at.is_null());
+        }
+
+        let users: Vec<User> = query.get_results(self.deref())?;
 
         Ok(users)
     }
@@ -29,8 +42,13 @@ impl UsersDb for DbConnection {
     }
 
     fn create_user(&self, user: &User) -> Result<User, DbError> {
+        let user = User {
+            password: hash_optional_password(&user.password)?,
+            ..user.clone()
+        };
+
         let user: User = diesel::insert_into(users::table)
-            .values(user)
+            .values(&user)
             .get_result(self.deref())?;
 
         Ok(user)
@@ -42,14 +60,78 @@ impl UsersDb for DbConnection {
         user_id: Uuid,
         user: &UpdateUser,
     ) -> Result<User, DbError> {
+        let user = UpdateUser {
+            firstname: user.firstname.clone(),
+            lastname: user.lastname.clone(),
+            nickname: user.nickname.clone(),
+            email: user.email.clone(),
+            role: user.role,
+            password: hash_optional_password(&user.password)?,
+        };
+
         let user: User = diesel::update(
             users::table.filter(users::team_id.eq(team_id).and(users::id.eq(user_id))),
         )
-        .set(user)
+        .set(&user)
+        .get_result(self.deref())?;
+
+        Ok(user)
+    }
+
+    fn deactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        let user: User = diesel::update(
+            users::table.filter(
+                users::team_id
+                    .eq(team_id)
+                    .and(users::id.eq(user_id))
+                    .and(users::deactivated_at.is_null()),
+            ),
+        )
+        .set(users::deactivated_at.eq(Utc::now().naive_utc()))
+        .get_result(self.deref())?;
+
+        Ok(user)
+    }
+
+    fn reactivate_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        let user: User = diesel::update(
+            users::table.filter(
+                users::team_id
+                    .eq(team_id)
+                    .and(users::id.eq(user_id))
+                    .and(users::deactivated_at.is_not_null()),
+            ),
+        )
+        .set(users::deactivated_at.eq(None::<NaiveDateTime>))
         .get_result(self.deref())?;
 
         Ok(user)
     }
+
+    fn delete_user(&self, team_id: Uuid, user_id: Uuid) -> Result<User, DbError> {
+        let user: User = diesel::delete(
+            users::table.filter(users::team_id.eq(team_id).and(users::id.eq(user_id))),
+        )
+        .get_result(self.deref())?;
+
+        Ok(user)
+    }
+
+    fn login_user(&self, team_id: Uuid, email: &str, password: &str) -> Result<User, DbError> {
+        let user: User = users::table
+            .filter(
+                users::team_id
+                    .eq(team_id)
+                    .and(users::email.eq(email))
+                    .and(users::deactivated_at.is_null()),
+            )
+            .get_result(self.deref())?;
+
+        match &user.password {
+            Some(stored) if verify_password(password, stored)? == PasswordMatch::Hashed => Ok(user),
+            _ => Err(DbError::NotFound),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -59,6 +141,7 @@ mod tests {
     use super::*;
     use crate::teams::{interface::TeamsDb, models::Team};
     use crate::test_utils::postgres::init_connection;
+    use crate::users::models::Role;
 
     #[test]
     fn test_get_users() {
@@ -89,8 +172,8 @@ mod tests {
                 })
                 .unwrap();
 
-            let users = conn.get_users(team_id).unwrap();
-            let users_2 = conn.get_users(team_id_2).unwrap();
+            let users = conn.get_users(team_id, false).unwrap();
+            let users_2 = conn.get_users(team_id_2, false).unwrap();
 
             assert_eq!(vec![user], users);
             assert_eq!(vec![user_2], users_2);
@@ -244,4 +327,174 @@ mod tests {
 
         assert_eq!(error, DbError::NotFound);
     }
+
+    #[test]
+    fn test_update_user_role_and_password() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let created_user = conn
+                .create_user(&User {
+                    team_id,
+                    password: Some(String::from("password")),
+                    ..Default::default()
+                })
+                .unwrap();
+
+            assert_eq!(created_user.role, Role::Member);
+            assert_ne!(created_user.password, Some(String::from("password")));
+
+            let user = conn
+                .update_user(
+                    team_id,
+                    created_user.id,
+                    &UpdateUser {
+                        role: Some(Role::Treasurer),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(user.role, Role::Treasurer);
+            assert_eq!(user.password, created_user.password);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_deactivate_user() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let user = conn.deactivate_user(team_id, user_id).unwrap();
+
+            assert!(!user.is_active());
+            assert!(conn.get_users(team_id, false).unwrap().is_empty());
+            assert_eq!(conn.get_users(team_id, true).unwrap(), vec![user]);
+            assert_eq!(
+                conn.deactivate_user(team_id, user_id).unwrap_err(),
+                DbError::NotFound
+            );
+
+            let user = conn.reactivate_user(team_id, user_id).unwrap();
+
+            assert!(user.is_active());
+            assert_eq!(conn.get_users(team_id, false).unwrap(), vec![user]);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_delete_user() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            conn.delete_user(team_id, user_id).unwrap();
+
+            assert_eq!(
+                conn.get_user(team_id, user_id).unwrap_err(),
+                DbError::NotFound
+            );
+            assert_eq!(
+                conn.delete_user(team_id, user_id).unwrap_err(),
+                DbError::NotFound
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_login_user() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    email: Some(String::from("email@gmail.com")),
+                    password: Some(String::from("password")),
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+
+            let user = conn
+                .login_user(team_id, "email@gmail.com", "password")
+                .unwrap();
+
+            assert_eq!(user.id, user_id);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_login_user_fails() {
+        let conn = init_connection();
+
+        conn.deref().test_transaction::<_, Error, _>(|| {
+            let team_id = conn.create_team(&Team::default()).unwrap().id;
+            let user_id = conn
+                .create_user(&User {
+                    team_id,
+                    email: Some(String::from("email@gmail.com")),
+                    password: Some(String::from("password")),
+                    ..Default::default()
+                })
+                .unwrap()
+                .id;
+            conn.create_user(&User {
+                id: Uuid::new_v4(),
+                team_id,
+                email: Some(String::from("other@gmail.com")),
+                ..Default::default()
+            })
+            .unwrap();
+
+            let error = conn
+                .login_user(team_id, "email@gmail.com", "wrong_password")
+                .unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            let error = conn.login_user(team_id, "other@gmail.com", "").unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            let error = conn
+                .login_user(Uuid::new_v4(), "email@gmail.com", "password")
+                .unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            conn.deactivate_user(team_id, user_id).unwrap();
+
+            let error = conn
+                .login_user(team_id, "email@gmail.com", "password")
+                .unwrap_err();
+            assert_eq!(error, DbError::NotFound);
+
+            Ok(())
+        })
+    }
 }
@@ -7,6 +7,17 @@ use super::{
     models::{UpdateUser, UpdateUserRequest, User},
 };
 use crate::api::models::ErrorResponse;
+use crate::audit::interface::AuditDb;
+use crate::database::postgres::DbError;
+use crate::payments::{interface::PaymentsDb, models::PaymentStatus};
+use crate::sanctions::{
+    interface::SanctionsDb,
+    models::ApprovalStatus,
+    utils::{
+        formatter::with_status,
+        parameters::{ParameterError, ParameterErrorKind},
+    },
+};
 
 #[derive(Serialize, Debug, PartialEq)]
 #[serde(untagged)]
@@ -15,14 +26,47 @@ pub enum ResultWrapper {
     User(User),
 }
 
+fn include_deactivated(request: &Request) -> Result<bool, ParameterError> {
+    match request.get_param("include_deactivated") {
+        Some(value) => value.parse::<bool>().map_err(|_| ParameterError {
+            parameter_name: String::from("include_deactivated"),
+            kind: ParameterErrorKind::UnvalidType {
+                expected_type: String::from("boolean"),
+            },
+        }),
+        None => Ok(false),
+    }
+}
+
+/// Counts the approved sanctions of the user which aren't fully paid yet.
+fn unpaid_sanctions<T>(db: &T, team_id: Uuid, user_id: Uuid) -> Result<usize, DbError>
+where
+    T: SanctionsDb + PaymentsDb,
+{
+    let sanctions = db
+        .get_sanctions(team_id, None, false)?
+        .into_iter()
+        .filter(|sanction| {
+            sanction.user_id == user_id && sanction.approval_status == ApprovalStatus::Approved
+        })
+        .collect();
+
+    Ok(
+        with_status(sanctions, &db.get_payments(team_id, Some(user_id))?)
+            .iter()
+            .filter(|sanction| sanction.status != PaymentStatus::Paid)
+            .count(),
+    )
+}
+
 #[allow(clippy::cognitive_complexity)]
 pub fn handle_request<T>(request: &Request, db: &T) -> Result<ResultWrapper, ErrorResponse>
 where
-    T: UsersDb,
+    T: UsersDb + SanctionsDb + PaymentsDb + AuditDb,
 {
     router!(request,
         (GET) (/teams/{team_id: Uuid}/users) => {
-            let result = db.get_users(team_id)?;
+            let result = db.get_users(team_id, include_deactivated(request)?)?;
 
             Ok(ResultWrapper::Users(result))
         },
@@ -46,6 +90,37 @@ where
 
             Ok(ResultWrapper::User(result))
         },
+        (POST) (/teams/{team_id: Uuid}/users/{user_id: Uuid}/deactivate) => {
+            let result = db.deactivate_user(team_id, user_id)?;
+
+            Ok(ResultWrapper::User(result))
+        },
+        (POST) (/teams/{team_id: Uuid}/users/{user_id: Uuid}/reactivate) => {
+            let result = db.reactivate_user(team_id, user_id)?;
+
+            Ok(ResultWrapper::User(result))
+        },
+        (DELETE) (/teams/{team_id: Uuid}/users/{user_id: Uuid}) => {
+            db.get_user(team_id, user_id)?;
+
+            let result = db.transaction(|| {
+                let unpaid = unpaid_sanctions(db, team_id, user_id)?;
+
+                if unpaid > 0 {
+                    return Ok(Err(unpaid));
+                }
+
+                db.delete_user(team_id, user_id).map(Ok)
+            })?;
+
+            match result {
+                Ok(user) => Ok(ResultWrapper::User(user)),
+                Err(unpaid) => Err(ErrorResponse::not_valid(format!(
+                    "The user {} still has {} unpaid sanctions and can only be deactivated",
+                    user_id, unpaid
+                ))),
+            }
+        },
         _ => {
             Err(ErrorResponse::not_found())
         }
@@ -58,7 +133,9 @@ mod tests {
 
     use super::*;
     use crate::api::models::{test_utils::RequestBuilder, ErrorKind};
-    use crate::test_utils::routes::{DbMock, UsersDbMock};
+    use crate::payments::models::Payment;
+    use crate::sanctions::models::Sanction;
+    use crate::test_utils::routes::{DbMock, PaymentsDbMock, SanctionsDbMock, UsersDbMock};
 
     #[test]
     fn test_get_users() {
@@ -222,4 +299,146 @@ mod tests {
 
         assert_eq!(error.kind, ErrorKind::NotFound);
     }
+
+    #[test]
+    fn test_get_users_with_uncorrect_parameter() {
+        let team_id = Uuid::new_v4();
+
+        let error = handle_request(
+            &RequestBuilder::get(format!("/teams/{}/users?include_deactivated=yes", team_id)),
+            &DbMock::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::BadParameter);
+    }
+
+    #[test]
+    fn test_deactivate_user() {
+        let user_id = Uuid::new_v4();
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/users/{}/deactivate", team_id, user_id),
+                &json!({}),
+            ),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(user_id));
+        assert_ne!(response["deactivated_at"], serde_json::Value::Null);
+
+        let error = handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/users/{}/deactivate", team_id, user_id),
+                &json!({}),
+            ),
+            &DbMock {
+                users_db: UsersDbMock::Deactivated,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_reactivate_user() {
+        let user_id = Uuid::new_v4();
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::post(
+                format!("/teams/{}/users/{}/reactivate", team_id, user_id),
+                &json!({}),
+            ),
+            &DbMock {
+                users_db: UsersDbMock::Deactivated,
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(user_id));
+        assert_eq!(response["deactivated_at"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_delete_user() {
+        let user_id = Uuid::new_v4();
+        let team_id = Uuid::new_v4();
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/users/{}", team_id, user_id)),
+            &DbMock::default(),
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(user_id));
+
+        let response = json!(handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/users/{}", team_id, user_id)),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    price: 500,
+                    ..Default::default()
+                })),
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                    amount: 500,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap());
+
+        assert_eq!(response["id"], json!(user_id));
+    }
+
+    #[test]
+    fn test_delete_user_fails() {
+        let user_id = Uuid::new_v4();
+        let team_id = Uuid::new_v4();
+
+        let error = handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/users/{}", team_id, user_id)),
+            &DbMock {
+                sanctions_db: SanctionsDbMock::SuccessWithSanction(Box::new(Sanction {
+                    user_id,
+                    price: 500,
+                    ..Default::default()
+                })),
+                payments_db: PaymentsDbMock::SuccessWithPayments(vec![Payment {
+                    amount: 200,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotValid);
+        assert_eq!(
+            error.description,
+            format!(
+                "The user {} still has 1 unpaid sanctions and can only be deactivated",
+                user_id
+            )
+        );
+
+        let error = handle_request(
+            &RequestBuilder::delete(format!("/teams/{}/users/{}", team_id, user_id)),
+            &DbMock {
+                users_db: UsersDbMock::NotFound,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::NotFound);
+    }
 }